@@ -0,0 +1,320 @@
+use crate::format::csv_field;
+use crate::json_stream::{ElementKind, JsonArrayReader, JsonStreamError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+
+/// A record of `mlperf_log_accuracy.json`: the output of the SUT for one
+/// sample, hex-encoded as loadgen received it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccuracyRecord {
+    pub seq_id: u64,
+    pub qsl_idx: u64,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    OddLength {
+        len: usize,
+    },
+    InvalidHex {
+        /// 0-based position of the character in the hex string.
+        position: usize,
+        found: char,
+    },
+    /// The payload is not a whole number of elements of the requested type.
+    Misaligned {
+        len: usize,
+        element_size: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::OddLength { len } => {
+                write!(f, "hex data has an odd number of digits ({})", len)
+            }
+            DecodeError::InvalidHex { position, found } => {
+                write!(f, "invalid hex digit '{}' at position {}", found, position)
+            }
+            DecodeError::Misaligned { len, element_size } => write!(
+                f,
+                "{} bytes is not a multiple of the element size ({})",
+                len, element_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes loadgen's hex encoding (upper case, two digits per byte).
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, DecodeError> {
+    if hex.len() % 2 != 0 {
+        return Err(DecodeError::OddLength { len: hex.len() });
+    }
+    let digit = |position: usize, c: u8| {
+        (c as char)
+            .to_digit(16)
+            .map(|d| d as u8)
+            .ok_or(DecodeError::InvalidHex {
+                position,
+                found: c as char,
+            })
+    };
+    hex.as_bytes()
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| Ok(digit(2 * i, pair[0])? << 4 | digit(2 * i + 1, pair[1])?))
+        .collect()
+}
+
+fn decode_le<const N: usize, T>(
+    bytes: &[u8],
+    from_le_bytes: fn([u8; N]) -> T,
+) -> Result<Vec<T>, DecodeError> {
+    if bytes.len() % N != 0 {
+        return Err(DecodeError::Misaligned {
+            len: bytes.len(),
+            element_size: N,
+        });
+    }
+    Ok(bytes
+        .chunks_exact(N)
+        .map(|chunk| from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+impl AccuracyRecord {
+    pub fn bytes(&self) -> Result<Vec<u8>, DecodeError> {
+        decode_hex(&self.data)
+    }
+
+    /// The payload as a little-endian `f32` array, e.g. classifier scores.
+    pub fn f32s(&self) -> Result<Vec<f32>, DecodeError> {
+        decode_le(&self.bytes()?, f32::from_le_bytes)
+    }
+
+    pub fn i32s(&self) -> Result<Vec<i32>, DecodeError> {
+        decode_le(&self.bytes()?, i32::from_le_bytes)
+    }
+
+    /// The payload as a little-endian `i64` array, e.g. generated token ids.
+    pub fn i64s(&self) -> Result<Vec<i64>, DecodeError> {
+        decode_le(&self.bytes()?, i64::from_le_bytes)
+    }
+
+    pub fn decode(&self, data_type: DataType) -> Result<DecodedData, DecodeError> {
+        Ok(match data_type {
+            DataType::Hex => DecodedData::Hex(self.data.clone()),
+            DataType::U8 => DecodedData::U8(self.bytes()?),
+            DataType::I32 => DecodedData::I32(self.i32s()?),
+            DataType::I64 => DecodedData::I64(self.i64s()?),
+            DataType::F32 => DecodedData::F32(self.f32s()?),
+        })
+    }
+}
+
+/// Type the payload of the records is decoded as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataType {
+    /// Keep the hex string as is.
+    #[default]
+    Hex,
+    U8,
+    I32,
+    I64,
+    F32,
+}
+
+impl FromStr for DataType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(DataType::Hex),
+            "u8" => Ok(DataType::U8),
+            "i32" => Ok(DataType::I32),
+            "i64" => Ok(DataType::I64),
+            "f32" => Ok(DataType::F32),
+            _ => Err(format!(
+                "Invalid data type: {}. Use 'hex', 'u8', 'i32', 'i64' or 'f32'.",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum DecodedData {
+    Hex(String),
+    U8(Vec<u8>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+}
+
+impl DecodedData {
+    /// Space separated values, as written in a CSV cell.
+    fn to_cell(&self) -> String {
+        fn join<T: ToString>(values: &[T]) -> String {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        match self {
+            DecodedData::Hex(hex) => hex.clone(),
+            DecodedData::U8(values) => join(values),
+            DecodedData::I32(values) => join(values),
+            DecodedData::I64(values) => join(values),
+            DecodedData::F32(values) => join(values),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AccuracyLogError {
+    Io(io::Error),
+    /// The records could not be read, e.g. a log cut short by a crashed run.
+    Read(JsonStreamError),
+    InvalidData {
+        seq_id: u64,
+        error: DecodeError,
+    },
+}
+
+impl fmt::Display for AccuracyLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccuracyLogError::Io(e) => write!(f, "{}", e),
+            AccuracyLogError::Read(e) => write!(f, "{}", e),
+            AccuracyLogError::InvalidData { seq_id, error } => {
+                write!(f, "invalid data (seq_id {}): {}", seq_id, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AccuracyLogError {}
+
+impl From<io::Error> for AccuracyLogError {
+    fn from(e: io::Error) -> Self {
+        AccuracyLogError::Io(e)
+    }
+}
+
+impl From<JsonStreamError> for AccuracyLogError {
+    fn from(e: JsonStreamError) -> Self {
+        match e {
+            JsonStreamError::Io(e) => AccuracyLogError::Io(e),
+            e => AccuracyLogError::Read(e),
+        }
+    }
+}
+
+impl From<AccuracyLogError> for io::Error {
+    fn from(e: AccuracyLogError) -> Self {
+        match e {
+            AccuracyLogError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// Reads the records of an accuracy log one at a time.
+///
+/// The log holds one record per sample and can be several GB, so the array
+/// is never loaded as a whole; only the record being parsed is in memory.
+/// Iteration stops after the first error.
+pub struct AccuracyLogReader<R: BufRead> {
+    inner: JsonArrayReader<R>,
+}
+
+impl<R: BufRead> AccuracyLogReader<R> {
+    pub fn new(reader: R) -> Self {
+        AccuracyLogReader {
+            inner: JsonArrayReader::new(reader, ElementKind::Record),
+        }
+    }
+}
+
+impl AccuracyLogReader<BufReader<File>> {
+    pub fn open(file_path: &str) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(file_path)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for AccuracyLogReader<R> {
+    type Item = Result<AccuracyRecord, JsonStreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_element()
+    }
+}
+
+/// Writes the records as JSON Lines (`jsonl`) or CSV (`csv`) with the
+/// payload decoded as `data_type`, one record at a time. Returns the number
+/// of records written.
+pub fn write_accuracy_log<R: BufRead, W: io::Write>(
+    reader: AccuracyLogReader<R>,
+    writer: &mut W,
+    format: &str,
+    data_type: DataType,
+) -> Result<usize, AccuracyLogError> {
+    if format != "jsonl" && format != "csv" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid format. Use 'jsonl' or 'csv'.",
+        )
+        .into());
+    }
+    if format == "csv" {
+        writeln!(writer, "seq_id,qsl_idx,data")?;
+    }
+
+    let mut count = 0;
+    for record in reader {
+        let record = record?;
+        let data = record
+            .decode(data_type)
+            .map_err(|error| AccuracyLogError::InvalidData {
+                seq_id: record.seq_id,
+                error,
+            })?;
+        if format == "csv" {
+            writeln!(
+                writer,
+                "{},{},{}",
+                record.seq_id,
+                record.qsl_idx,
+                csv_field(&data.to_cell())
+            )?;
+        } else {
+            let line = serde_json::json!({
+                "seq_id": record.seq_id,
+                "qsl_idx": record.qsl_idx,
+                "data": data,
+            });
+            writeln!(writer, "{}", line)?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+pub fn save_accuracy_log<W: io::Write>(
+    input_file: &str,
+    writer: &mut W,
+    format: &str,
+    data_type: DataType,
+) -> io::Result<usize> {
+    let reader = AccuracyLogReader::open(input_file)?;
+    Ok(write_accuracy_log(reader, writer, format, data_type)?)
+}
@@ -0,0 +1,197 @@
+use crate::error::ParseError;
+use crate::loadgen::Scenario;
+use crate::log_detail::TestSettings;
+use crate::log_summary::lexer::SourceLocation;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+
+/// A `model.Scenario.key = value` line of `mlperf.conf`, `user.conf` or
+/// `audit.config`. `model` and `scenario` may be the `*` wildcard.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfEntry {
+    pub model: String,
+    pub scenario: String,
+    pub key: String,
+    pub value: String,
+    /// File the entry was read from, when loaded with `Conf::from_file`.
+    pub file: Option<String>,
+    /// 1-based line number of the entry in its file.
+    pub line_no: usize,
+}
+
+/// Parsed loadgen config file(s).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Conf {
+    /// Entries of each file, in the order the files were loaded.
+    pub files: Vec<Vec<ConfEntry>>,
+}
+
+fn invalid_line(text: &str, offset: usize, message: &str) -> ParseError {
+    ParseError::InvalidConf {
+        path: None,
+        location: SourceLocation::at(text, offset),
+        message: message.to_string(),
+    }
+}
+
+impl Conf {
+    /// Parses the config format loadgen reads. Blank lines and `#` comments
+    /// are ignored, as is anything after a `#` on an entry line.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        for (i, raw) in text.split('\n').enumerate() {
+            let line_offset = offset;
+            offset += raw.len() + 1;
+
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let indent = raw.len() - raw.trim_start().len();
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid_line(text, line_offset + indent, "expected `=`"))?;
+            let mut parts = name.trim().splitn(3, '.');
+            let (model, scenario, key) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(m), Some(s), Some(k)) if !m.is_empty() && !s.is_empty() && !k.is_empty() => {
+                    (m, s, k)
+                }
+                _ => {
+                    return Err(invalid_line(
+                        text,
+                        line_offset + indent,
+                        "expected `model.Scenario.key`",
+                    ))
+                }
+            };
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(invalid_line(
+                    text,
+                    line_offset + indent,
+                    "missing value after `=`",
+                ));
+            }
+            entries.push(ConfEntry {
+                model: model.to_string(),
+                scenario: scenario.to_string(),
+                key: key.to_string(),
+                value: value.to_string(),
+                file: None,
+                line_no: i + 1,
+            });
+        }
+        Ok(Conf {
+            files: vec![entries],
+        })
+    }
+
+    pub fn from_file(file_path: &str) -> Result<Self, ParseError> {
+        let text =
+            fs::read_to_string(file_path).map_err(|e| ParseError::from(e).with_path(file_path))?;
+        let mut conf = Self::parse(&text).map_err(|e| e.with_path(file_path))?;
+        for entry in conf.files.iter_mut().flatten() {
+            entry.file = Some(file_path.to_string());
+        }
+        Ok(conf)
+    }
+
+    /// Adds the files of a config loaded after this one, e.g. `user.conf`
+    /// after `mlperf.conf`; they take precedence over the existing ones.
+    pub fn extend(&mut self, other: Conf) {
+        self.files.extend(other.files);
+    }
+
+    /// Entries of every file, in the order they were loaded.
+    pub fn entries(&self) -> impl Iterator<Item = &ConfEntry> {
+        self.files.iter().flatten()
+    }
+
+    /// Value loadgen uses for `key`. Loadgen applies the files one after the
+    /// other, so the last file setting `key` for the model and scenario wins,
+    /// whatever the pattern of its line. Within a file, `model.Scenario.key`
+    /// is looked up first, then `*.Scenario.key`, `model.*.key` and
+    /// `*.*.key`, and for each pattern the last line wins.
+    pub fn get(&self, model: &str, scenario: Scenario, key: &str) -> Option<&ConfEntry> {
+        let scenario = scenario.as_str();
+        self.files.iter().rev().find_map(|entries| {
+            [(model, scenario), ("*", scenario), (model, "*"), ("*", "*")]
+                .iter()
+                .find_map(|(m, s)| {
+                    entries
+                        .iter()
+                        .rev()
+                        .find(|e| e.model == *m && e.scenario == *s && e.key == key)
+                })
+        })
+    }
+
+    /// Every key that applies to `model` and `scenario`, resolved with `get`,
+    /// in the order the keys first appear.
+    pub fn resolve(&self, model: &str, scenario: Scenario) -> IndexMap<String, &ConfEntry> {
+        self.entries()
+            .filter(|e| {
+                (e.model == model || e.model == "*")
+                    && (e.scenario == scenario.as_str() || e.scenario == "*")
+            })
+            .filter_map(|e| {
+                let entry = self.get(model, scenario, &e.key)?;
+                Some((e.key.clone(), entry))
+            })
+            .collect()
+    }
+
+    /// Compares the keys this config sets for `model` against the effective
+    /// settings of a run, reporting those loadgen did not apply. Keys that
+    /// cannot be recovered from a detail log are skipped.
+    pub fn check(&self, model: &str, settings: &TestSettings) -> Vec<ConfMismatch> {
+        let effective: IndexMap<&str, String> = settings.conf_entries().into_iter().collect();
+        self.resolve(model, settings.scenario)
+            .into_values()
+            // an override of 0 means "use the QSL's count", not 0 samples
+            .filter(|entry| {
+                !(entry.key == "performance_sample_count_override" && entry.value == "0")
+            })
+            .filter_map(|entry| {
+                let value = effective.get(entry.key.as_str())?;
+                (!same_value(&entry.value, value)).then(|| ConfMismatch {
+                    entry: entry.clone(),
+                    effective: value.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Numbers are compared with a small tolerance because some settings
+/// (e.g. the SingleStream `target_latency`) are derived from others.
+fn same_value(conf: &str, effective: &str) -> bool {
+    match (conf.parse::<f64>(), effective.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a == b || (a - b).abs() <= 1e-6 * a.abs().max(b.abs()),
+        _ => conf == effective,
+    }
+}
+
+/// A config entry whose value differs from the one loadgen ran with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfMismatch {
+    pub entry: ConfEntry,
+    pub effective: String,
+}
+
+impl fmt::Display for ConfMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.entry.file {
+            Some(file) => write!(f, "{}:{}: ", file, self.entry.line_no)?,
+            None => write!(f, "line {}: ", self.entry.line_no)?,
+        }
+        write!(
+            f,
+            "{}.{}.{} = {}, but loadgen ran with {}",
+            self.entry.model, self.entry.scenario, self.entry.key, self.entry.value, self.effective
+        )
+    }
+}
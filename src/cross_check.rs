@@ -0,0 +1,187 @@
+use crate::error::ParseError;
+use crate::log_detail::{parse_mlperf_log_detail_file, MLLogEntry};
+use crate::log_summary::model::{Dict, DictValue};
+use crate::log_summary::parse_mlperf_results_dict;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use serde_value::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Relative difference below which two numbers are considered equal. The
+/// summary prints rounded values while the detail log keeps full precision.
+const TOLERANCE: f64 = 1e-4;
+
+/// Summary fields and the detail log key holding the same value.
+const FIELDS: &[(&str, &str)] = &[
+    ("mlperf_results_summary.scenario", "effective_scenario"),
+    ("mlperf_results_summary.mode", "effective_test_mode"),
+    ("mlperf_results_summary.result_is", "result_validity"),
+    (
+        "mlperf_results_summary.samples_per_second",
+        "result_samples_per_second",
+    ),
+    (
+        "mlperf_results_summary.tokens_per_second",
+        "result_tokens_per_second",
+    ),
+    (
+        "mlperf_results_summary.scheduled_samples_per_second",
+        "result_scheduled_samples_per_sec",
+    ),
+    (
+        "mlperf_results_summary.completed_samples_per_second",
+        "result_completed_samples_per_sec",
+    ),
+    (
+        "additional_stats.qps_w/_loadgen_overhead",
+        "result_qps_with_loadgen_overhead",
+    ),
+    (
+        "additional_stats.qps_w/o_loadgen_overhead",
+        "result_qps_without_loadgen_overhead",
+    ),
+    ("additional_stats.min_latency_ns", "result_min_latency_ns"),
+    ("additional_stats.max_latency_ns", "result_max_latency_ns"),
+    ("additional_stats.mean_latency_ns", "result_mean_latency_ns"),
+];
+
+/// A field the summary and the detail log of a run disagree on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldMismatch {
+    /// Dotted path of the field in the summary dict.
+    pub field: String,
+    /// Key of the matching detail log entry.
+    pub detail_key: String,
+    pub summary: String,
+    pub detail: String,
+}
+
+impl fmt::Display for FieldMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: summary has {}, detail log ({}) has {}",
+            self.field, self.summary, self.detail_key, self.detail
+        )
+    }
+}
+
+fn lookup<'a>(summary: &'a Dict, field: &str) -> Option<&'a Value> {
+    let mut keys = field.split('.');
+    let mut value = summary.get(keys.next()?)?;
+    for key in keys {
+        value = match value {
+            DictValue::Dict(dict) => dict.get(key)?,
+            _ => return None,
+        };
+    }
+    // `Result is : VALID` carries its details along with the value
+    match value {
+        DictValue::Value(value) => Some(value),
+        DictValue::Dict(dict) => match dict.get("value")? {
+            DictValue::Value(value) => Some(value),
+            _ => None,
+        },
+        DictValue::List(_) => None,
+    }
+}
+
+fn summary_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::I64(n) => Some(n.to_string()),
+        Value::U64(n) => Some(n.to_string()),
+        Value::F32(n) => Some(n.to_string()),
+        Value::F64(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn summary_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::I64(n) => Some(*n as f64),
+        Value::U64(n) => Some(*n as f64),
+        Value::F32(n) => Some(*n as f64),
+        Value::F64(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Integers (latencies, counts) must match exactly, other numbers within
+/// `TOLERANCE`.
+fn same_value(summary: &Value, detail: &JsonValue) -> bool {
+    let summary_int = match summary {
+        Value::I64(n) => Some(*n as i128),
+        Value::U64(n) => Some(*n as i128),
+        _ => None,
+    };
+    let detail_int = detail
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| detail.as_u64().map(i128::from));
+    if let (Some(a), Some(b)) = (summary_int, detail_int) {
+        return a == b;
+    }
+    match (summary_number(summary), detail.as_f64()) {
+        (Some(a), Some(b)) => a == b || (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()),
+        _ => match detail {
+            JsonValue::String(s) => summary_text(summary).as_deref() == Some(s.as_str()),
+            other => summary_text(summary) == Some(other.to_string()),
+        },
+    }
+}
+
+/// `result_99.90_percentile_latency_ns` -> `additional_stats.99_90_percentile_latency_ns`
+fn percentile_field(key: &str) -> Option<String> {
+    let name = key.strip_prefix("result_")?;
+    name.contains("_percentile_latency_ns")
+        .then(|| format!("additional_stats.{}", name.replace('.', "_")))
+}
+
+/// Compares the fields a summary (as returned by `parse_mlperf_results_dict`)
+/// shares with the detail log of the same run. Fields missing on either side
+/// are not compared.
+pub fn cross_check_results(summary: &Dict, entries: &[MLLogEntry]) -> Vec<FieldMismatch> {
+    // the last entry wins when a key is logged twice
+    let detail: BTreeMap<&str, &JsonValue> =
+        entries.iter().map(|e| (e.key.as_str(), &e.value)).collect();
+
+    let percentiles: Vec<(String, &str)> = detail
+        .keys()
+        .filter_map(|key| Some((percentile_field(key)?, *key)))
+        .collect();
+    let fields = FIELDS
+        .iter()
+        .map(|(field, key)| (field.to_string(), *key))
+        .chain(percentiles);
+
+    fields
+        .filter_map(|(field, key)| {
+            let summary_value = lookup(summary, &field)?;
+            let detail_value = detail.get(key)?;
+            (!same_value(summary_value, detail_value)).then(|| FieldMismatch {
+                summary: summary_text(summary_value)
+                    .unwrap_or_else(|| format!("{:?}", summary_value)),
+                detail: match detail_value {
+                    JsonValue::String(s) => s.clone(),
+                    other => other.to_string(),
+                },
+                detail_key: key.to_string(),
+                field,
+            })
+        })
+        .collect()
+}
+
+/// Parses the summary and detail logs of a run and reports every field they
+/// disagree on, e.g. because they were copied from different runs.
+pub fn cross_check(
+    summary_file: &str,
+    detail_file: &str,
+) -> Result<Vec<FieldMismatch>, ParseError> {
+    let summary = parse_mlperf_results_dict(summary_file)?;
+    let entries = parse_mlperf_log_detail_file(detail_file)?;
+    Ok(cross_check_results(&summary, &entries))
+}
@@ -0,0 +1,225 @@
+use crate::error::ParseError;
+use crate::log_summary::lexer::SourceLocation;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A rustc-style rendering of a `ParseError`:
+///
+/// ```text
+/// error: unexpected section separator
+///  --> mlperf_log_summary.txt:5:1
+///   |
+/// 4 | SUT name : A
+/// 5 | ================
+///   | ^^^^^^^^^^^^^^^^ expected a line or a blank line
+///   |
+///   = hint: missing blank line after section; insert an empty line before the separator
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub path: Option<PathBuf>,
+    /// Line the caret points at.
+    pub location: Option<SourceLocation>,
+    /// Line printed above `location` to give context.
+    pub context: Option<SourceLocation>,
+    /// Number of carets under `location`.
+    pub span: usize,
+    pub label: Option<String>,
+    pub hint: Option<String>,
+}
+
+/// Human readable name of a grammar token kind.
+pub fn describe_token(kind: &str) -> &str {
+    match kind {
+        "SectionSeparatorLine" => "section separator",
+        "Line" => "line",
+        "EndOfSection" => "blank line",
+        "EOF" => "end of file",
+        other => other,
+    }
+}
+
+fn describe_expected(expected: &[String]) -> Option<String> {
+    let names: Vec<&str> = expected.iter().map(|e| describe_token(e)).collect();
+    match names.as_slice() {
+        [] => None,
+        [one] => Some(format!("expected a {}", one)),
+        [init @ .., last] => Some(format!("expected a {} or a {}", init.join(", a "), last)),
+    }
+}
+
+fn has(expected: &[String], kind: &str) -> bool {
+    expected.iter().any(|e| e == kind)
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(e: &ParseError) -> Self {
+        let path = e.path().map(|p| p.to_path_buf());
+        match e {
+            ParseError::Lexical {
+                location, at_eof, ..
+            } => Diagnostic {
+                message: "invalid input".to_string(),
+                path,
+                span: span_of(location),
+                location: Some(location.clone()),
+                context: None,
+                label: Some("this line cannot be read".to_string()),
+                hint: at_eof
+                    .then(|| "the last line of the log must end with a newline".to_string()),
+            },
+            ParseError::UnexpectedToken {
+                location,
+                previous,
+                token,
+                expected,
+                ..
+            } => {
+                let hint = match token.as_str() {
+                    "SectionSeparatorLine" if has(expected, "EndOfSection") => Some(
+                        "missing blank line after section; insert an empty line before the separator",
+                    ),
+                    "Line" if has(expected, "SectionSeparatorLine") => {
+                        Some("section titles must be enclosed by `====` separator lines")
+                    }
+                    "Line" if has(expected, "EndOfSection") => Some(
+                        "a note outside of a section must be a single line followed by a blank line",
+                    ),
+                    "EndOfSection" if has(expected, "Line") => {
+                        Some("blank line inside a section header; expected the section title")
+                    }
+                    _ => None,
+                };
+                Diagnostic {
+                    message: format!("unexpected {}", describe_token(token)),
+                    path,
+                    span: span_of(location),
+                    location: Some(location.clone()),
+                    context: previous.as_deref().cloned(),
+                    label: describe_expected(expected),
+                    hint: hint.map(|h| h.to_string()),
+                }
+            }
+            ParseError::UnexpectedEof {
+                location, expected, ..
+            } => Diagnostic {
+                message: "unexpected end of file".to_string(),
+                path,
+                // point right after the last character of the line
+                span: 1,
+                location: Some(location.clone()),
+                context: None,
+                label: describe_expected(expected),
+                hint: Some(
+                    "the log looks truncated; every section must end with a blank line".to_string(),
+                ),
+            },
+            ParseError::InvalidJson {
+                location, error, ..
+            } => Diagnostic {
+                message: "invalid JSON in detail log entry".to_string(),
+                path,
+                span: 1,
+                location: Some(location.clone()),
+                context: None,
+                label: Some(error.to_string()),
+                hint: None,
+            },
+            ParseError::InvalidConf {
+                location, message, ..
+            } => Diagnostic {
+                message: "invalid config line".to_string(),
+                path,
+                span: span_of(location),
+                location: Some(location.clone()),
+                context: None,
+                label: Some(message.clone()),
+                hint: Some("config lines look like `model.Scenario.key = value`".to_string()),
+            },
+            ParseError::DuplicateKey { duplicate, .. } => Diagnostic {
+                message: duplicate.to_string(),
+                path,
+                span: 0,
+                location: None,
+                context: None,
+                label: None,
+                hint: Some(
+                    "keep the first or last value, or collect them into a list, to accept duplicates"
+                        .to_string(),
+                ),
+            },
+            ParseError::Io { error, .. } => Diagnostic {
+                message: error.to_string(),
+                path,
+                span: 0,
+                location: None,
+                context: None,
+                label: None,
+                hint: None,
+            },
+        }
+    }
+}
+
+fn span_of(location: &SourceLocation) -> usize {
+    location
+        .source
+        .chars()
+        .skip(location.column.saturating_sub(1))
+        .collect::<String>()
+        .trim_end()
+        .chars()
+        .count()
+        .max(1)
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "error: {}", self.message)?;
+
+        let path = self
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<input>".to_string());
+
+        let location = match &self.location {
+            Some(location) => location,
+            None => {
+                write!(f, "--> {}", path)?;
+                if let Some(hint) = &self.hint {
+                    write!(f, "\n = hint: {}", hint)?;
+                }
+                return Ok(());
+            }
+        };
+
+        let width = location.line.to_string().len();
+        let gutter = " ".repeat(width);
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter, path, location.line, location.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        if let Some(context) = &self.context {
+            writeln!(f, "{:>width$} | {}", context.line, context.source)?;
+        }
+        writeln!(f, "{:>width$} | {}", location.line, location.source)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(location.column.saturating_sub(1)),
+            "^".repeat(self.span)
+        )?;
+        if let Some(label) = &self.label {
+            write!(f, " {}", label)?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\n{} |\n{} = hint: {}", gutter, gutter, hint)?;
+        }
+        Ok(())
+    }
+}
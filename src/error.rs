@@ -0,0 +1,151 @@
+use crate::diagnostic::Diagnostic;
+use crate::log_detail::{DetailParseError, MLLOG_PREFIX};
+use crate::log_summary::lexer::SourceLocation;
+use crate::log_summary::model::DuplicateKey;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Error returned by the summary, detail log and config parsers.
+///
+/// Every variant that points into a log carries the `SourceLocation` of the
+/// offending line and, when the input came from a file, its path.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Input the lexer could not split into lines (e.g. a final line without `\n`).
+    Lexical {
+        path: Option<PathBuf>,
+        location: SourceLocation,
+        /// The input is the last line and it is not terminated by a newline.
+        at_eof: bool,
+    },
+    UnexpectedToken {
+        path: Option<PathBuf>,
+        location: SourceLocation,
+        /// Closest non-blank line before `location`, shown for context.
+        previous: Option<Box<SourceLocation>>,
+        /// Kind of the token that was found (`Line`, `EndOfSection`, ...).
+        token: String,
+        /// Token kinds the grammar would have accepted instead.
+        expected: Vec<String>,
+    },
+    UnexpectedEof {
+        path: Option<PathBuf>,
+        location: SourceLocation,
+        expected: Vec<String>,
+    },
+    InvalidJson {
+        path: Option<PathBuf>,
+        location: SourceLocation,
+        error: serde_json::Error,
+    },
+    /// A line of a loadgen config file that is not `model.Scenario.key = value`.
+    InvalidConf {
+        path: Option<PathBuf>,
+        location: SourceLocation,
+        message: String,
+    },
+    /// A key appeared twice under `DuplicateKeyPolicy::Error`.
+    DuplicateKey {
+        path: Option<PathBuf>,
+        duplicate: DuplicateKey,
+    },
+    Io {
+        path: Option<PathBuf>,
+        error: io::Error,
+    },
+}
+
+impl ParseError {
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ParseError::Lexical { path, .. }
+            | ParseError::UnexpectedToken { path, .. }
+            | ParseError::UnexpectedEof { path, .. }
+            | ParseError::InvalidJson { path, .. }
+            | ParseError::InvalidConf { path, .. }
+            | ParseError::DuplicateKey { path, .. }
+            | ParseError::Io { path, .. } => path.as_deref(),
+        }
+    }
+
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            ParseError::Lexical { location, .. }
+            | ParseError::UnexpectedToken { location, .. }
+            | ParseError::UnexpectedEof { location, .. }
+            | ParseError::InvalidJson { location, .. }
+            | ParseError::InvalidConf { location, .. } => Some(location),
+            ParseError::DuplicateKey { .. } | ParseError::Io { .. } => None,
+        }
+    }
+
+    /// Attaches the path of the file the input was read from.
+    pub fn with_path(mut self, file_path: impl Into<PathBuf>) -> Self {
+        let file_path = Some(file_path.into());
+        match &mut self {
+            ParseError::Lexical { path, .. }
+            | ParseError::UnexpectedToken { path, .. }
+            | ParseError::UnexpectedEof { path, .. }
+            | ParseError::InvalidJson { path, .. }
+            | ParseError::InvalidConf { path, .. }
+            | ParseError::DuplicateKey { path, .. }
+            | ParseError::Io { path, .. } => *path = file_path,
+        }
+        self
+    }
+}
+
+/// Renders the error as a `Diagnostic`, pointing at the offending line.
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Diagnostic::from(self))
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::InvalidJson { error, .. } => Some(error),
+            ParseError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        ParseError::Io { path: None, error }
+    }
+}
+
+impl From<DetailParseError> for ParseError {
+    fn from(e: DetailParseError) -> Self {
+        match e {
+            DetailParseError::Io(error) => ParseError::Io { path: None, error },
+            DetailParseError::InvalidJson {
+                line_no,
+                line,
+                error,
+            } => ParseError::InvalidJson {
+                path: None,
+                location: SourceLocation {
+                    column: error.column() + MLLOG_PREFIX.len(),
+                    source: line,
+                    line: line_no,
+                },
+                error,
+            },
+        }
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Io { error, path: None } => error,
+            ParseError::Io { ref error, .. } => io::Error::new(error.kind(), e),
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use serde_value::Value;
+
+/// Quotes a field containing a separator, a quote or a line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escapes the pipes and line breaks of a markdown table cell.
+pub fn markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Renders a dict leaf as a table cell.
+pub fn cell(value: &Value) -> String {
+    match value {
+        Value::Bool(b) => b.to_string(),
+        Value::U8(n) => n.to_string(),
+        Value::U16(n) => n.to_string(),
+        Value::U32(n) => n.to_string(),
+        Value::U64(n) => n.to_string(),
+        Value::I8(n) => n.to_string(),
+        Value::I16(n) => n.to_string(),
+        Value::I32(n) => n.to_string(),
+        Value::I64(n) => n.to_string(),
+        Value::F32(n) => n.to_string(),
+        Value::F64(n) => n.to_string(),
+        Value::Char(c) => c.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Unit | Value::Option(None) => String::new(),
+        Value::Option(Some(v)) | Value::Newtype(v) => cell(v),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Rounds away float noise, `-34.559999999999945` -> `-34.56`.
+pub fn format_float(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    match text.contains('.') {
+        true => text.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => text,
+    }
+}
@@ -0,0 +1,286 @@
+use serde::de::{DeserializeOwned, IgnoredAny};
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// What the elements of a streamed JSON array are, used to name them in
+/// error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementKind {
+    /// A record of `mlperf_log_accuracy.json`.
+    Record,
+    /// An event of `mlperf_log_trace.json`.
+    Event,
+}
+
+impl ElementKind {
+    /// Name of the log holding elements of this kind.
+    pub fn log(&self) -> &'static str {
+        match self {
+            ElementKind::Record => "accuracy log",
+            ElementKind::Event => "trace log",
+        }
+    }
+}
+
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementKind::Record => write!(f, "record"),
+            ElementKind::Event => write!(f, "event"),
+        }
+    }
+}
+
+/// Error of the readers streaming the elements of a JSON array, such as
+/// `AccuracyLogReader` and `TraceLogReader`.
+#[derive(Debug)]
+pub enum JsonStreamError {
+    Io(io::Error),
+    /// The input is not a JSON array, e.g. a log cut short by a crashed run.
+    Malformed {
+        kind: ElementKind,
+        /// 0-based index of the element being read.
+        index: usize,
+        expected: &'static str,
+        found: Option<char>,
+    },
+    InvalidJson {
+        kind: ElementKind,
+        index: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for JsonStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonStreamError::Io(e) => write!(f, "{}", e),
+            JsonStreamError::Malformed {
+                kind,
+                index,
+                expected,
+                found,
+            } => {
+                let found = found.map_or("end of file".to_string(), |c| format!("'{}'", c));
+                write!(
+                    f,
+                    "malformed {} ({} {}): expected {}, found {}",
+                    kind.log(),
+                    kind,
+                    index,
+                    expected,
+                    found
+                )
+            }
+            JsonStreamError::InvalidJson { kind, index, error } => {
+                write!(f, "JSON parsing error ({} {}): {}", kind, index, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonStreamError {}
+
+impl From<io::Error> for JsonStreamError {
+    fn from(e: io::Error) -> Self {
+        JsonStreamError::Io(e)
+    }
+}
+
+impl From<JsonStreamError> for io::Error {
+    fn from(e: JsonStreamError) -> Self {
+        match e {
+            JsonStreamError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReaderState {
+    Start,
+    First,
+    Rest,
+    Done,
+}
+
+/// Deserializes the elements of a JSON array one at a time, so that only the
+/// element being parsed is in memory. Iteration stops after the first error.
+pub(crate) struct JsonArrayReader<R: BufRead> {
+    reader: R,
+    kind: ElementKind,
+    /// Key of the array when it is wrapped in an object, as in
+    /// `{"traceEvents": [...]}`.
+    key: Option<&'static str>,
+    index: usize,
+    state: ReaderState,
+}
+
+impl<R: BufRead> JsonArrayReader<R> {
+    pub(crate) fn new(reader: R, kind: ElementKind) -> Self {
+        JsonArrayReader {
+            reader,
+            kind,
+            key: None,
+            index: 0,
+            state: ReaderState::Start,
+        }
+    }
+
+    /// Reads either a bare array or the array under `key` of an object. Keys
+    /// after the array are not read.
+    pub(crate) fn in_object(reader: R, kind: ElementKind, key: &'static str) -> Self {
+        JsonArrayReader {
+            key: Some(key),
+            ..Self::new(reader, kind)
+        }
+    }
+
+    fn malformed(&self, expected: &'static str, found: Option<u8>) -> JsonStreamError {
+        JsonStreamError::Malformed {
+            kind: self.kind,
+            index: self.index,
+            expected,
+            found: found.map(char::from),
+        }
+    }
+
+    /// Skips whitespace and returns the next byte without consuming it.
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) => {
+                    let byte = buf[i];
+                    self.reader.consume(i);
+                    return Ok(Some(byte));
+                }
+                None => {
+                    let len = buf.len();
+                    self.reader.consume(len);
+                }
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8, expected: &'static str) -> Result<(), JsonStreamError> {
+        match self.peek()? {
+            Some(b) if b == byte => {
+                self.reader.consume(1);
+                Ok(())
+            }
+            found => Err(self.malformed(expected, found)),
+        }
+    }
+
+    /// Values that end with a delimiter (objects, arrays, strings) leave the
+    /// reader right after them; a bare number would lose the byte after it.
+    fn read_value<T: DeserializeOwned>(&mut self) -> Result<T, JsonStreamError> {
+        let mut de = serde_json::Deserializer::from_reader(&mut self.reader);
+        T::deserialize(&mut de).map_err(|error| JsonStreamError::InvalidJson {
+            kind: self.kind,
+            index: self.index,
+            error,
+        })
+    }
+
+    /// Skips a value. Numbers and literals are read up to the delimiter after
+    /// them, which `read_value` would consume along with them.
+    fn skip_value(&mut self) -> Result<(), JsonStreamError> {
+        if matches!(self.peek()?, Some(b'"' | b'{' | b'[')) {
+            return self.read_value::<IgnoredAny>().map(|_| ());
+        }
+        let mut token = Vec::new();
+        loop {
+            let buf = self.reader.fill_buf()?;
+            let end = buf
+                .iter()
+                .position(|b| matches!(b, b',' | b'}' | b']') || b.is_ascii_whitespace());
+            let len = end.unwrap_or(buf.len());
+            token.extend_from_slice(&buf[..len]);
+            self.reader.consume(len);
+            if end.is_some() || len == 0 {
+                break;
+            }
+        }
+        serde_json::from_slice::<IgnoredAny>(&token)
+            .map(|_| ())
+            .map_err(|error| JsonStreamError::InvalidJson {
+                kind: self.kind,
+                index: self.index,
+                error,
+            })
+    }
+
+    /// Moves past the `[` of the array, skipping the keys before it when it is
+    /// wrapped in an object.
+    fn read_start(&mut self) -> Result<(), JsonStreamError> {
+        let Some(key) = self.key else {
+            return self.expect(b'[', "'['");
+        };
+        if self.peek()? == Some(b'[') {
+            return self.expect(b'[', "'['");
+        }
+        self.expect(b'{', "'[' or '{'")?;
+        loop {
+            if self.peek()? != Some(b'"') {
+                let found = self.peek()?;
+                return Err(self.malformed("an object key", found));
+            }
+            let name: String = self.read_value()?;
+            self.expect(b':', "':'")?;
+            if name == key {
+                return self.expect(b'[', "'['");
+            }
+            self.skip_value()?;
+            match self.peek()? {
+                Some(b',') => self.reader.consume(1),
+                // no more keys, reported as a missing key on the next turn
+                Some(b'}') => {}
+                found => return Err(self.malformed("',' or '}'", found)),
+            }
+        }
+    }
+
+    fn read_element<T: DeserializeOwned>(&mut self) -> Result<Option<T>, JsonStreamError> {
+        if self.state == ReaderState::Start {
+            self.read_start()?;
+            self.state = ReaderState::First;
+        }
+
+        match (self.state, self.peek()?) {
+            (_, Some(b']')) => return Ok(None),
+            (ReaderState::Rest, Some(b',')) => {
+                self.reader.consume(1);
+                // tolerate a trailing comma
+                if self.peek()? == Some(b']') {
+                    return Ok(None);
+                }
+            }
+            (ReaderState::First, Some(_)) => {}
+            (ReaderState::First, found) => return Err(self.malformed("an element or ']'", found)),
+            (_, found) => return Err(self.malformed("',' or ']'", found)),
+        }
+
+        let element = self.read_value()?;
+        self.index += 1;
+        self.state = ReaderState::Rest;
+        Ok(Some(element))
+    }
+
+    pub(crate) fn next_element<T: DeserializeOwned>(
+        &mut self,
+    ) -> Option<Result<T, JsonStreamError>> {
+        if self.state == ReaderState::Done {
+            return None;
+        }
+        let result = self.read_element().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.state = ReaderState::Done;
+        }
+        result
+    }
+}
@@ -1,7 +1,30 @@
+pub mod accuracy_log;
+pub mod conf;
+pub mod cross_check;
+pub mod diagnostic;
+pub mod error;
+pub mod format;
+pub mod json_stream;
+pub mod loadgen;
 pub mod log_detail;
 pub mod log_summary;
+pub mod submission;
+pub mod table;
+pub mod trace_log;
 
+pub use accuracy_log::{AccuracyLogReader, AccuracyRecord};
+pub use conf::Conf;
+pub use cross_check::{cross_check, FieldMismatch};
+pub use error::ParseError;
+pub use loadgen::{Scenario, TestMode};
 pub use log_detail::{
-    parse_mlperf_log_detail, parse_mlperf_log_detail_file, save_log_detail, MLLogEntry,
+    issues, parse_mlperf_log_detail, parse_mlperf_log_detail_file, save_log_detail, settings_diff,
+    DetailEvent, DetailLogReader, DetailParseError, Issue, MLLogEntry, SettingDiff, TestSettings,
 };
-pub use log_summary::{parse_log_summary, parse_mlperf_results_file, save_summary};
+pub use log_summary::summary::MlperfSummary;
+pub use log_summary::{
+    parse_log_summary, parse_log_summary_dict, parse_log_summary_file, parse_mlperf_results_dict,
+    parse_mlperf_results_file, save_summary, save_summary_with,
+};
+pub use submission::{scan_submission, RunKey, RunLogs, Scan};
+pub use trace_log::{TraceEvent, TraceLogReader};
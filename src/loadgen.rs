@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Loadgen `TestScenario`, spelled the way loadgen prints it in both the
+/// summary (`Scenario : Offline`) and detail (`effective_scenario`) logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Scenario {
+    Offline,
+    Server,
+    SingleStream,
+    MultiStream,
+}
+
+impl Scenario {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scenario::Offline => "Offline",
+            Scenario::Server => "Server",
+            Scenario::SingleStream => "SingleStream",
+            Scenario::MultiStream => "MultiStream",
+        }
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Scenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "Offline" => Ok(Scenario::Offline),
+            "Server" => Ok(Scenario::Server),
+            "SingleStream" => Ok(Scenario::SingleStream),
+            "MultiStream" => Ok(Scenario::MultiStream),
+            other => Err(format!("Unknown scenario: {}", other)),
+        }
+    }
+}
+
+/// Loadgen `TestMode`, as printed in `effective_test_mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TestMode {
+    SubmissionRun,
+    AccuracyOnly,
+    PerformanceOnly,
+    FindPeakPerformance,
+}
+
+impl TestMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestMode::SubmissionRun => "SubmissionRun",
+            TestMode::AccuracyOnly => "AccuracyOnly",
+            TestMode::PerformanceOnly => "PerformanceOnly",
+            TestMode::FindPeakPerformance => "FindPeakPerformance",
+        }
+    }
+
+    /// Value of the `mode` key in `mlperf.conf` / `user.conf`.
+    pub fn conf_value(&self) -> u8 {
+        match self {
+            TestMode::SubmissionRun => 0,
+            TestMode::AccuracyOnly => 1,
+            TestMode::PerformanceOnly => 2,
+            TestMode::FindPeakPerformance => 3,
+        }
+    }
+
+    pub fn from_conf_value(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(TestMode::SubmissionRun),
+            1 => Some(TestMode::AccuracyOnly),
+            2 => Some(TestMode::PerformanceOnly),
+            3 => Some(TestMode::FindPeakPerformance),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TestMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TestMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "SubmissionRun" => Ok(TestMode::SubmissionRun),
+            "AccuracyOnly" => Ok(TestMode::AccuracyOnly),
+            "PerformanceOnly" => Ok(TestMode::PerformanceOnly),
+            "FindPeakPerformance" => Ok(TestMode::FindPeakPerformance),
+            other => Err(format!("Unknown test mode: {}", other)),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::log_detail::MLLogEntry;
+use crate::log_summary::summary::Validity;
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+/// A detail log entry classified by its key.
+///
+/// Settings and results keep the key without its `requested_`, `effective_`
+/// or `result_` prefix in `name`, e.g. `min_duration_ms` for
+/// `effective_min_duration_ms`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetailEvent {
+    /// `loadgen_*` build information (version, build date, git log, file hashes).
+    LoadgenInfo {
+        name: String,
+        value: JsonValue,
+    },
+    TestDatetime(String),
+    SutName(String),
+    QslName(String),
+    /// Setting as requested by the benchmark (`requested_*`).
+    RequestedSetting {
+        name: String,
+        value: JsonValue,
+    },
+    /// Setting loadgen actually ran with (`effective_*`).
+    EffectiveSetting {
+        name: String,
+        value: JsonValue,
+    },
+    /// `result_validity`
+    ResultValidity(Validity),
+    /// Boolean result check, e.g. `result_min_duration_met`.
+    ResultCheck {
+        name: String,
+        met: bool,
+    },
+    /// Numeric result, e.g. `result_samples_per_second`.
+    ResultMetric {
+        name: String,
+        value: f64,
+    },
+    /// `power_begin`, with the timestamp loadgen printed.
+    PowerBegin(String),
+    /// `power_end`, with the timestamp loadgen printed.
+    PowerEnd(String),
+    /// Entry logged with `metadata.is_error` set.
+    Error {
+        key: String,
+        message: String,
+    },
+    /// Entry logged with `metadata.is_warning` set.
+    Warning {
+        key: String,
+        message: String,
+    },
+    Unknown {
+        key: String,
+        value: JsonValue,
+    },
+}
+
+/// A well-known key whose value does not have the expected type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventError {
+    pub key: String,
+    pub line_no: usize,
+    pub expected: &'static str,
+    pub found: JsonValue,
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid value for '{}' (line {}): expected {}, found {}",
+            self.key, self.line_no, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for EventError {}
+
+fn flag(entry: &MLLogEntry, name: &str) -> bool {
+    entry
+        .metadata
+        .get(name)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Error and warning messages are usually strings, but loadgen logs some of
+/// them as structured values.
+fn message_of(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl TryFrom<&MLLogEntry> for DetailEvent {
+    type Error = EventError;
+
+    fn try_from(entry: &MLLogEntry) -> Result<Self, EventError> {
+        let key = entry.key.as_str();
+        let value = &entry.value;
+        let invalid = |expected| EventError {
+            key: entry.key.clone(),
+            line_no: entry.line_no,
+            expected,
+            found: value.clone(),
+        };
+        let string = || {
+            value
+                .as_str()
+                .map(|s| s.to_string())
+                .ok_or_else(|| invalid("a string"))
+        };
+
+        if flag(entry, "is_error") {
+            return Ok(DetailEvent::Error {
+                key: entry.key.clone(),
+                message: message_of(value),
+            });
+        }
+        if flag(entry, "is_warning") {
+            return Ok(DetailEvent::Warning {
+                key: entry.key.clone(),
+                message: message_of(value),
+            });
+        }
+
+        let event = match key {
+            "test_datetime" => DetailEvent::TestDatetime(string()?),
+            "sut_name" => DetailEvent::SutName(string()?),
+            "qsl_name" => DetailEvent::QslName(string()?),
+            "power_begin" => DetailEvent::PowerBegin(string()?),
+            "power_end" => DetailEvent::PowerEnd(string()?),
+            "result_validity" => match value.as_str() {
+                Some("VALID") => DetailEvent::ResultValidity(Validity::Valid),
+                Some("INVALID") => DetailEvent::ResultValidity(Validity::Invalid),
+                _ => return Err(invalid("VALID or INVALID")),
+            },
+            _ => {
+                if let Some(name) = key.strip_prefix("loadgen_") {
+                    DetailEvent::LoadgenInfo {
+                        name: name.to_string(),
+                        value: value.clone(),
+                    }
+                } else if let Some(name) = key.strip_prefix("requested_") {
+                    DetailEvent::RequestedSetting {
+                        name: name.to_string(),
+                        value: value.clone(),
+                    }
+                } else if let Some(name) = key.strip_prefix("effective_") {
+                    DetailEvent::EffectiveSetting {
+                        name: name.to_string(),
+                        value: value.clone(),
+                    }
+                } else if let Some(name) = key.strip_prefix("result_") {
+                    match value {
+                        JsonValue::Bool(met) => DetailEvent::ResultCheck {
+                            name: name.to_string(),
+                            met: *met,
+                        },
+                        JsonValue::Number(n) => DetailEvent::ResultMetric {
+                            name: name.to_string(),
+                            value: n.as_f64().ok_or_else(|| invalid("a number"))?,
+                        },
+                        _ => DetailEvent::Unknown {
+                            key: entry.key.clone(),
+                            value: value.clone(),
+                        },
+                    }
+                } else {
+                    DetailEvent::Unknown {
+                        key: entry.key.clone(),
+                        value: value.clone(),
+                    }
+                }
+            }
+        };
+        Ok(event)
+    }
+}
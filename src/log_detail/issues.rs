@@ -0,0 +1,85 @@
+use crate::log_detail::{DetailEvent, MLLogEntry};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// An entry loadgen logged as an error or a warning.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub key: String,
+    pub message: String,
+    pub time_ms: f64,
+    /// Loadgen source file that logged the entry (`metadata.file`).
+    pub file: Option<String>,
+    /// Line in `file` (`metadata.line_no`).
+    pub source_line: Option<u64>,
+    /// 1-based line number of the entry in the detail log.
+    pub line_no: usize,
+}
+
+impl Issue {
+    fn new(entry: &MLLogEntry, severity: Severity, key: String, message: String) -> Self {
+        Issue {
+            severity,
+            key,
+            message,
+            time_ms: entry.time_ms,
+            file: entry
+                .metadata
+                .get("file")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            source_line: entry.metadata.get("line_no").and_then(|v| v.as_u64()),
+            line_no: entry.line_no,
+        }
+    }
+}
+
+/// `error: [12.345ms] error_invalid_config: Multiple conf files (test_settings_internal.cc:706)`
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: [{}ms] {}: {}",
+            self.severity, self.time_ms, self.key, self.message
+        )?;
+        match (&self.file, self.source_line) {
+            (Some(file), Some(line)) => write!(f, " ({}:{})", file, line),
+            (Some(file), None) => write!(f, " ({})", file),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Collects the entries flagged with `metadata.is_error` or
+/// `metadata.is_warning`, in log order.
+pub fn issues(entries: &[MLLogEntry]) -> Vec<Issue> {
+    entries
+        .iter()
+        .filter_map(|entry| match DetailEvent::try_from(entry).ok()? {
+            DetailEvent::Error { key, message } => {
+                Some(Issue::new(entry, Severity::Error, key, message))
+            }
+            DetailEvent::Warning { key, message } => {
+                Some(Issue::new(entry, Severity::Warning, key, message))
+            }
+            _ => None,
+        })
+        .collect()
+}
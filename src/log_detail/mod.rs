@@ -0,0 +1,254 @@
+pub mod event;
+pub mod issues;
+pub mod settings;
+
+use crate::error::ParseError;
+use crate::table::Table;
+pub use event::{DetailEvent, EventError};
+pub use issues::{issues, Issue, Severity};
+use log::warn;
+use serde_json::Value as JsonValue;
+pub use settings::{settings_diff, SettingDiff, SettingsError, TestSettings};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+pub(crate) const MLLOG_PREFIX: &str = ":::MLLOG ";
+
+#[derive(Debug, serde::Serialize)]
+pub struct MLLogEntry {
+    pub key: String,
+    pub value: JsonValue,
+    pub time_ms: f64,
+    pub namespace: String,
+    pub event_type: String,
+    pub metadata: BTreeMap<String, JsonValue>,
+    /// 1-based line number of the entry in the detail log.
+    #[serde(skip)]
+    pub line_no: usize,
+}
+
+impl MLLogEntry {
+    fn from_json(map: &serde_json::Map<String, JsonValue>, line_no: usize) -> Self {
+        MLLogEntry {
+            key: map
+                .get("key")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            value: map.get("value").cloned().unwrap_or(JsonValue::Null),
+            time_ms: map
+                .get("time_ms")
+                .and_then(|v| v.as_f64())
+                .unwrap_or_default(),
+            namespace: map
+                .get("namespace")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            event_type: map
+                .get("event_type")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            metadata: map
+                .get("metadata")
+                .and_then(|v| v.as_object())
+                .map(|o| o.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                .unwrap_or_default(),
+            line_no,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DetailParseError {
+    Io(io::Error),
+    InvalidJson {
+        line_no: usize,
+        line: String,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for DetailParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetailParseError::Io(e) => write!(f, "{}", e),
+            DetailParseError::InvalidJson { line_no, error, .. } => {
+                write!(f, "JSON parsing error (line {}): {}", line_no, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DetailParseError {}
+
+impl From<io::Error> for DetailParseError {
+    fn from(e: io::Error) -> Self {
+        DetailParseError::Io(e)
+    }
+}
+
+/// Reads `:::MLLOG` entries from a detail log one line at a time.
+///
+/// Lines without the `:::MLLOG` prefix are skipped with a warning, so only
+/// a single line is held in memory regardless of the log size.
+pub struct DetailLogReader<R: BufRead> {
+    reader: R,
+    line_no: usize,
+    buf: String,
+}
+
+impl<R: BufRead> DetailLogReader<R> {
+    pub fn new(reader: R) -> Self {
+        DetailLogReader {
+            reader,
+            line_no: 0,
+            buf: String::new(),
+        }
+    }
+}
+
+impl DetailLogReader<BufReader<File>> {
+    pub fn open(file_path: &str) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(file_path)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for DetailLogReader<R> {
+    type Item = Result<MLLogEntry, DetailParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.line_no += 1,
+                Err(e) => return Some(Err(e.into())),
+            }
+            let line = self.buf.trim_end_matches(['\n', '\r']);
+
+            // Remove ":::MLLOG " prefix and parse JSON
+            let Some(json_str) = line.strip_prefix(MLLOG_PREFIX) else {
+                warn!("Invalid line[{}]: {}", self.line_no, line);
+                continue;
+            };
+            match serde_json::from_str::<JsonValue>(json_str) {
+                Ok(JsonValue::Object(map)) => {
+                    return Some(Ok(MLLogEntry::from_json(&map, self.line_no)));
+                }
+                Ok(_) => {
+                    warn!("Not a JSON object[{}]: {}", self.line_no, line);
+                }
+                Err(error) => {
+                    return Some(Err(DetailParseError::InvalidJson {
+                        line_no: self.line_no,
+                        line: line.to_string(),
+                        error,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+fn collect_entries<R: BufRead>(reader: DetailLogReader<R>) -> Result<Vec<MLLogEntry>, ParseError> {
+    reader
+        .map(|entry| entry.map_err(ParseError::from))
+        .collect()
+}
+
+/// Parses every entry of a detail log. The first line with malformed JSON is
+/// returned as `ParseError::InvalidJson`; use `DetailLogReader` to skip such
+/// lines instead.
+pub fn parse_mlperf_log_detail(text: &str) -> Result<Vec<MLLogEntry>, ParseError> {
+    collect_entries(DetailLogReader::new(text.as_bytes()))
+}
+
+pub fn parse_mlperf_log_detail_file(file_path: &str) -> Result<Vec<MLLogEntry>, ParseError> {
+    DetailLogReader::open(file_path)
+        .map_err(ParseError::from)
+        .and_then(collect_entries)
+        .map_err(|e| e.with_path(file_path))
+}
+
+pub fn save_log_detail_as_json<W: io::Write>(input_file: &str, output: &mut W) -> io::Result<()> {
+    let entries = parse_mlperf_log_detail_file(input_file)?;
+    serde_json::to_writer_pretty(output, &entries)?;
+    Ok(())
+}
+
+pub fn save_log_detail_as_yaml<W: io::Write>(input_file: &str, output: &mut W) -> io::Result<()> {
+    let entries = parse_mlperf_log_detail_file(input_file)?;
+    serde_yaml::to_writer(output, &entries).map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// Metadata columns `save_log_detail` writes in the "csv" format.
+pub const DEFAULT_METADATA_COLUMNS: &[&str] = &["is_error", "is_warning", "file", "line_no"];
+
+fn json_cell(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => String::new(),
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Writes one row per entry with its key, value, time_ms and event_type,
+/// followed by the given metadata fields as `metadata.<name>` columns.
+pub fn write_log_detail_csv<W: io::Write>(
+    entries: &[MLLogEntry],
+    output: &mut W,
+    metadata_columns: &[&str],
+) -> io::Result<()> {
+    let mut columns: Vec<String> = ["key", "value", "time_ms", "event_type"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    columns.extend(metadata_columns.iter().map(|c| format!("metadata.{}", c)));
+
+    let mut table = Table::new(columns);
+    for entry in entries {
+        let mut row = vec![
+            entry.key.clone(),
+            json_cell(&entry.value),
+            entry.time_ms.to_string(),
+            entry.event_type.clone(),
+        ];
+        row.extend(
+            metadata_columns
+                .iter()
+                .map(|c| entry.metadata.get(*c).map(json_cell).unwrap_or_default()),
+        );
+        table.rows.push(row);
+    }
+    table.write_csv(output)
+}
+
+pub fn save_log_detail_as_csv<W: io::Write>(
+    input_file: &str,
+    output: &mut W,
+    metadata_columns: &[&str],
+) -> io::Result<()> {
+    let entries = parse_mlperf_log_detail_file(input_file)?;
+    write_log_detail_csv(&entries, output, metadata_columns)
+}
+
+pub fn save_log_detail<W: io::Write>(
+    input_file: &str,
+    output: &mut W,
+    format: &str,
+) -> io::Result<()> {
+    match format {
+        "json" => save_log_detail_as_json(input_file, output),
+        "yaml" => save_log_detail_as_yaml(input_file, output),
+        "csv" => save_log_detail_as_csv(input_file, output, DEFAULT_METADATA_COLUMNS),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid format. Use 'json', 'yaml' or 'csv'.",
+        )),
+    }
+}
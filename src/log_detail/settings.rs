@@ -0,0 +1,487 @@
+use crate::loadgen::{Scenario, TestMode};
+use crate::log_detail::{DetailEvent, MLLogEntry};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::io;
+
+/// Error returned when the `effective_*` entries of a detail log cannot be
+/// turned into `TestSettings`. `field` is the setting name without prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsError {
+    MissingField {
+        field: String,
+    },
+    InvalidType {
+        field: String,
+        expected: &'static str,
+        found: JsonValue,
+    },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::MissingField { field } => {
+                write!(f, "missing setting 'effective_{}'", field)
+            }
+            SettingsError::InvalidType {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "invalid type for setting 'effective_{}': expected {}, found {}",
+                field, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+/// Settings loadgen ran with, rebuilt from the `effective_*` entries of a
+/// detail log.
+///
+/// Only `scenario` and `mode` are required; the other settings depend on the
+/// loadgen version (and on the log not being truncated) so they are optional.
+/// Effective settings without a field of their own end up in `other`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TestSettings {
+    pub scenario: Scenario,
+    pub mode: TestMode,
+    pub samples_per_query: Option<u64>,
+    pub target_qps: Option<f64>,
+    pub target_latency_ns: Option<u64>,
+    pub target_latency_percentile: Option<f64>,
+    pub max_async_queries: Option<u64>,
+    pub target_duration_ms: Option<u64>,
+    pub min_duration_ms: Option<u64>,
+    pub max_duration_ms: Option<u64>,
+    pub min_query_count: Option<u64>,
+    pub max_query_count: Option<u64>,
+    pub min_sample_count: Option<u64>,
+    pub qsl_rng_seed: Option<u64>,
+    pub sample_index_rng_seed: Option<u64>,
+    pub schedule_rng_seed: Option<u64>,
+    pub accuracy_log_rng_seed: Option<u64>,
+    pub accuracy_log_probability: Option<f64>,
+    pub accuracy_log_sampling_target: Option<u64>,
+    pub print_timestamps: Option<bool>,
+    pub performance_issue_unique: Option<bool>,
+    pub performance_issue_same: Option<bool>,
+    pub performance_issue_same_index: Option<u64>,
+    pub performance_sample_count: Option<u64>,
+    pub other: IndexMap<String, JsonValue>,
+}
+
+/// Values of the `effective_*` entries keyed by setting name. When a key is
+/// logged more than once, the last value wins.
+pub fn effective_settings(entries: &[MLLogEntry]) -> IndexMap<String, JsonValue> {
+    entries
+        .iter()
+        .filter_map(|e| match DetailEvent::try_from(e).ok()? {
+            DetailEvent::EffectiveSetting { name, value } => Some((name, value)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Takes settings out of the effective settings map, leaving the ones
+/// without a field of their own.
+struct Settings(IndexMap<String, JsonValue>);
+
+impl Settings {
+    fn take<T>(
+        &mut self,
+        field: &str,
+        expected: &'static str,
+        convert: impl FnOnce(&JsonValue) -> Option<T>,
+    ) -> Result<Option<T>, SettingsError> {
+        match self.0.shift_remove(field) {
+            None => Ok(None),
+            Some(value) => match convert(&value) {
+                Some(v) => Ok(Some(v)),
+                None => Err(SettingsError::InvalidType {
+                    field: field.to_string(),
+                    expected,
+                    found: value,
+                }),
+            },
+        }
+    }
+
+    fn u64(&mut self, field: &str) -> Result<Option<u64>, SettingsError> {
+        self.take(field, "an unsigned integer", |v| {
+            v.as_u64().or_else(|| {
+                v.as_f64()
+                    .filter(|f| f.fract() == 0.0 && *f >= 0.0 && *f <= u64::MAX as f64)
+                    .map(|f| f as u64)
+            })
+        })
+    }
+
+    fn f64(&mut self, field: &str) -> Result<Option<f64>, SettingsError> {
+        self.take(field, "a number", |v| v.as_f64())
+    }
+
+    /// Loadgen logs flags as `true`/`false`, older versions as `1`/`0`.
+    fn bool(&mut self, field: &str) -> Result<Option<bool>, SettingsError> {
+        self.take(field, "a boolean", |v| {
+            v.as_bool().or_else(|| match v.as_u64() {
+                Some(0) => Some(false),
+                Some(1) => Some(true),
+                _ => None,
+            })
+        })
+    }
+
+    fn parsed<T: std::str::FromStr>(&mut self, field: &str) -> Result<T, SettingsError> {
+        self.take(field, "a known name", |v| v.as_str()?.parse().ok())?
+            .ok_or_else(|| SettingsError::MissingField {
+                field: field.to_string(),
+            })
+    }
+}
+
+impl TryFrom<&[MLLogEntry]> for TestSettings {
+    type Error = SettingsError;
+
+    fn try_from(entries: &[MLLogEntry]) -> Result<Self, Self::Error> {
+        let mut s = Settings(effective_settings(entries));
+        Ok(TestSettings {
+            scenario: s.parsed("scenario")?,
+            mode: s.parsed("test_mode")?,
+            samples_per_query: s.u64("samples_per_query")?,
+            target_qps: s.f64("target_qps")?,
+            target_latency_ns: s.u64("target_latency_ns")?,
+            target_latency_percentile: s.f64("target_latency_percentile")?,
+            max_async_queries: s.u64("max_async_queries")?,
+            target_duration_ms: s.u64("target_duration_ms")?,
+            min_duration_ms: s.u64("min_duration_ms")?,
+            max_duration_ms: s.u64("max_duration_ms")?,
+            min_query_count: s.u64("min_query_count")?,
+            max_query_count: s.u64("max_query_count")?,
+            min_sample_count: s.u64("min_sample_count")?,
+            qsl_rng_seed: s.u64("qsl_rng_seed")?,
+            sample_index_rng_seed: s.u64("sample_index_rng_seed")?,
+            schedule_rng_seed: s.u64("schedule_rng_seed")?,
+            accuracy_log_rng_seed: s.u64("accuracy_log_rng_seed")?,
+            accuracy_log_probability: s.f64("accuracy_log_probability")?,
+            accuracy_log_sampling_target: s.u64("accuracy_log_sampling_target")?,
+            print_timestamps: s.bool("print_timestamps")?,
+            performance_issue_unique: s.bool("performance_issue_unique")?,
+            performance_issue_same: s.bool("performance_issue_same")?,
+            performance_issue_same_index: s.u64("performance_issue_same_index")?,
+            performance_sample_count: s.u64("performance_sample_count")?,
+            other: s.0,
+        })
+    }
+}
+
+fn flag(value: bool) -> String {
+    u8::from(value).to_string()
+}
+
+impl TestSettings {
+    /// Target latency in ms as `user.conf` expects it. SingleStream and
+    /// MultiStream only log the expected latency as `target_qps`, so it is
+    /// derived from there when `target_latency_ns` is not set.
+    fn conf_target_latency_ms(&self) -> Option<f64> {
+        match self.target_latency_ns {
+            Some(ns) if ns > 0 => Some(ns as f64 / 1e6),
+            _ => match self.scenario {
+                Scenario::SingleStream | Scenario::MultiStream => self
+                    .target_qps
+                    .filter(|qps| *qps > 0.0)
+                    .map(|qps| 1000.0 / qps),
+                _ => None,
+            },
+        }
+    }
+
+    /// `(key, value)` pairs of a `user.conf` that reproduces these settings,
+    /// using the key names and units loadgen reads (`min_duration` in ms,
+    /// `mode` as a number, flags as 0/1).
+    pub fn conf_entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![("mode", self.mode.conf_value().to_string())];
+        let mut push = |key, value: Option<String>| {
+            if let Some(value) = value {
+                entries.push((key, value));
+            }
+        };
+
+        match self.scenario {
+            Scenario::Offline | Scenario::Server => {
+                push("target_qps", self.target_qps.map(|v| v.to_string()))
+            }
+            Scenario::SingleStream | Scenario::MultiStream => {}
+        }
+        match self.scenario {
+            Scenario::Server | Scenario::SingleStream | Scenario::MultiStream => push(
+                "target_latency",
+                self.conf_target_latency_ms().map(|v| v.to_string()),
+            ),
+            Scenario::Offline => {}
+        }
+        push(
+            "target_latency_percentile",
+            self.target_latency_percentile.map(|v| v.to_string()),
+        );
+        if self.scenario == Scenario::MultiStream {
+            push(
+                "samples_per_query",
+                self.samples_per_query.map(|v| v.to_string()),
+            );
+        }
+        push(
+            "max_async_queries",
+            self.max_async_queries.map(|v| v.to_string()),
+        );
+        push("min_duration", self.min_duration_ms.map(|v| v.to_string()));
+        push("max_duration", self.max_duration_ms.map(|v| v.to_string()));
+        push(
+            "min_query_count",
+            self.min_query_count.map(|v| v.to_string()),
+        );
+        push(
+            "max_query_count",
+            self.max_query_count.map(|v| v.to_string()),
+        );
+        push(
+            "performance_sample_count_override",
+            self.performance_sample_count.map(|v| v.to_string()),
+        );
+        push("qsl_rng_seed", self.qsl_rng_seed.map(|v| v.to_string()));
+        push(
+            "sample_index_rng_seed",
+            self.sample_index_rng_seed.map(|v| v.to_string()),
+        );
+        push(
+            "schedule_rng_seed",
+            self.schedule_rng_seed.map(|v| v.to_string()),
+        );
+        push(
+            "accuracy_log_rng_seed",
+            self.accuracy_log_rng_seed.map(|v| v.to_string()),
+        );
+        push(
+            "accuracy_log_probability",
+            self.accuracy_log_probability.map(|v| v.to_string()),
+        );
+        push(
+            "accuracy_log_sampling_target",
+            self.accuracy_log_sampling_target.map(|v| v.to_string()),
+        );
+        push("print_timestamps", self.print_timestamps.map(flag));
+        push(
+            "performance_issue_unique",
+            self.performance_issue_unique.map(flag),
+        );
+        push(
+            "performance_issue_same",
+            self.performance_issue_same.map(flag),
+        );
+        push(
+            "performance_issue_same_index",
+            self.performance_issue_same_index.map(|v| v.to_string()),
+        );
+        entries
+    }
+
+    /// Writes `model.Scenario.key = value` lines reproducing these settings.
+    /// `model` may be `*` to apply them to any model.
+    pub fn write_conf<W: io::Write>(&self, model: &str, writer: &mut W) -> io::Result<()> {
+        for (key, value) in self.conf_entries() {
+            writeln!(writer, "{}.{}.{} = {}", model, self.scenario, key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A setting loadgen ran with a different value than the one requested,
+/// e.g. `requested_min_duration_ms: 600000` vs `effective_min_duration_ms: 10000`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingDiff {
+    /// Requested setting name without the `requested_` prefix.
+    pub name: String,
+    /// Effective setting name without the `effective_` prefix. It differs
+    /// from `name` for scenario specific settings, e.g. `server_target_qps`
+    /// is applied as `target_qps`.
+    pub effective_name: String,
+    pub requested: JsonValue,
+    pub effective: JsonValue,
+}
+
+impl fmt::Display for SettingDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: requested {}, effective ", self.name, self.requested)?;
+        if self.effective_name != self.name {
+            write!(f, "{} ", self.effective_name)?;
+        }
+        write!(f, "{}", self.effective)
+    }
+}
+
+/// How loadgen turns a requested setting into the effective one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Conversion {
+    Same,
+    /// An expected latency in ns becomes `target_qps = 1e9 / latency`.
+    LatencyToQps,
+    /// 0 keeps the default, other values are applied as is.
+    Override,
+}
+
+/// Requested settings whose effective counterpart has another name, keyed by
+/// the scenario they apply to (`None` for every scenario). Loadgen only logs
+/// the requested settings of the scenario being run.
+const RENAMED_SETTINGS: &[(Option<Scenario>, &str, &str, Conversion)] = &[
+    (
+        Some(Scenario::SingleStream),
+        "single_stream_expected_latency_ns",
+        "target_qps",
+        Conversion::LatencyToQps,
+    ),
+    (
+        Some(Scenario::SingleStream),
+        "single_stream_target_latency_percentile",
+        "target_latency_percentile",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::MultiStream),
+        "multi_stream_expected_latency_ns",
+        "target_qps",
+        Conversion::LatencyToQps,
+    ),
+    (
+        Some(Scenario::MultiStream),
+        "multi_stream_target_latency_percentile",
+        "target_latency_percentile",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::MultiStream),
+        "multi_stream_samples_per_query",
+        "samples_per_query",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::Server),
+        "server_target_qps",
+        "target_qps",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::Server),
+        "server_target_latency_ns",
+        "target_latency_ns",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::Server),
+        "server_target_latency_percentile",
+        "target_latency_percentile",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::Server),
+        "server_max_async_queries",
+        "max_async_queries",
+        Conversion::Same,
+    ),
+    (
+        Some(Scenario::Offline),
+        "offline_expected_qps",
+        "target_qps",
+        Conversion::Same,
+    ),
+    (
+        None,
+        "performance_sample_count_override",
+        "performance_sample_count",
+        Conversion::Override,
+    ),
+];
+
+/// Effective name of a requested setting and how its value is converted.
+fn effective_name(scenario: Option<Scenario>, name: &str) -> (&str, Conversion) {
+    RENAMED_SETTINGS
+        .iter()
+        .find(|(s, requested, _, _)| *requested == name && (s.is_none() || *s == scenario))
+        .map_or(
+            (name, Conversion::Same),
+            |&(_, _, effective, conversion)| (effective, conversion),
+        )
+}
+
+/// `1000` and `1000.0` are the same setting value.
+fn same_value(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => {
+            a == b || matches!((a.as_f64(), b.as_f64()), (Some(a), Some(b)) if a == b)
+        }
+        _ => a == b,
+    }
+}
+
+/// Whether loadgen applied `requested` as `effective`. `None` when the
+/// requested value does not apply (an override left at 0).
+fn is_applied(
+    conversion: Conversion,
+    requested: &JsonValue,
+    effective: &JsonValue,
+) -> Option<bool> {
+    match conversion {
+        Conversion::Same => Some(same_value(requested, effective)),
+        Conversion::LatencyToQps => {
+            let (latency, qps) = (requested.as_f64()?, effective.as_f64()?);
+            // the effective QPS is logged rounded
+            Some(latency > 0.0 && (1e9 / latency - qps).abs() <= 1e-6 * qps.abs())
+        }
+        Conversion::Override => match requested.as_u64() {
+            Some(0) => None,
+            _ => Some(same_value(requested, effective)),
+        },
+    }
+}
+
+/// Pairs every `requested_*` setting with its `effective_*` counterpart and
+/// returns those whose values differ, in the order they were requested.
+///
+/// Scenario specific settings are paired with the setting loadgen applies
+/// them to, e.g. `requested_server_target_qps` with `effective_target_qps`,
+/// and expected latencies are compared as the QPS loadgen derives from them.
+/// Settings logged only on one side are not reported. When a key is logged
+/// more than once, the last value wins.
+pub fn settings_diff(entries: &[MLLogEntry]) -> Vec<SettingDiff> {
+    let requested: IndexMap<String, JsonValue> = entries
+        .iter()
+        .filter_map(|e| match DetailEvent::try_from(e).ok()? {
+            DetailEvent::RequestedSetting { name, value } => Some((name, value)),
+            _ => None,
+        })
+        .collect();
+    let effective = effective_settings(entries);
+    let scenario = requested
+        .get("scenario")
+        .or_else(|| effective.get("scenario"))
+        .and_then(|v| v.as_str()?.parse().ok());
+
+    requested
+        .into_iter()
+        .filter_map(|(name, requested)| {
+            let (effective_name, conversion) = effective_name(scenario, &name);
+            let effective_name = effective_name.to_string();
+            let effective = effective.get(&effective_name)?;
+            (!is_applied(conversion, &requested, effective)?).then(|| SettingDiff {
+                effective_name,
+                name,
+                requested,
+                effective: effective.clone(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,220 @@
+use super::model::{flatten_dict, Dict};
+use super::parse_log_summary_file;
+use super::summary::as_f64;
+use crate::error::ParseError;
+use crate::format::{cell, format_float};
+use serde::Serialize;
+use serde_value::Value;
+use std::fmt;
+
+/// A key whose value differs between two summaries. `None` when the key is
+/// missing from one of them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange {
+    /// Dotted key, e.g. `mlperf_results_summary.samples_per_second`.
+    pub key: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl ValueChange {
+    /// `new - old` when both values are numbers.
+    pub fn absolute(&self) -> Option<f64> {
+        Some(as_f64(self.new.as_ref()?)? - as_f64(self.old.as_ref()?)?)
+    }
+
+    /// Change relative to the old value, `0.05` for +5%. `None` when the old
+    /// value is 0.
+    pub fn relative(&self) -> Option<f64> {
+        let old = as_f64(self.old.as_ref()?)?;
+        if old == 0.0 {
+            return None;
+        }
+        Some(self.absolute()? / old.abs())
+    }
+}
+
+fn format_delta(delta: f64) -> String {
+    match delta >= 0.0 {
+        true => format!("+{}", format_float(delta)),
+        false => format_float(delta),
+    }
+}
+
+impl fmt::Display for ValueChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |value: &Option<Value>| value.as_ref().map_or("(missing)".to_string(), cell);
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.key,
+            side(&self.old),
+            side(&self.new)
+        )?;
+        match (self.absolute(), self.relative()) {
+            (Some(abs), Some(rel)) => {
+                write!(f, " ({}, {:+.2}%)", format_delta(abs), rel * 100.0)
+            }
+            (Some(abs), None) => write!(f, " ({})", format_delta(abs)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Aligns the keys of two summary dicts across sections and returns those
+/// whose value changed, in the order of `old` followed by the keys only
+/// found in `new`.
+pub fn diff_summaries(old: &Dict, new: &Dict) -> Vec<ValueChange> {
+    let old = flatten_dict(old);
+    let mut new = flatten_dict(new);
+    let mut changes = Vec::new();
+    for (key, old_value) in old {
+        let new_value = new.shift_remove(&key);
+        if new_value.as_ref() != Some(&old_value) {
+            changes.push(ValueChange {
+                key,
+                old: Some(old_value),
+                new: new_value,
+            });
+        }
+    }
+    changes.extend(new.into_iter().map(|(key, new_value)| ValueChange {
+        key,
+        old: None,
+        new: Some(new_value),
+    }));
+    changes
+}
+
+pub fn diff_summary_files(old_file: &str, new_file: &str) -> Result<Vec<ValueChange>, ParseError> {
+    let old = parse_log_summary_file(old_file)?.to_dict();
+    let new = parse_log_summary_file(new_file)?.to_dict();
+    Ok(diff_summaries(&old, &new))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Increase,
+    Decrease,
+}
+
+/// Largest change allowed for a key before it counts as a regression, e.g.
+/// a throughput dropping by more than 2%.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Threshold {
+    /// Dotted key or trailing part of it, `samples_per_second` matches
+    /// `mlperf_results_summary.samples_per_second`.
+    pub key: String,
+    pub direction: Direction,
+    pub percent: f64,
+}
+
+impl Threshold {
+    pub fn new(key: &str, direction: Direction, percent: f64) -> Self {
+        Threshold {
+            key: key.to_string(),
+            direction,
+            percent,
+        }
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        key == self.key
+            || key
+                .strip_suffix(self.key.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    }
+
+    /// Whether `change` breaks this threshold. A key missing from one of the
+    /// summaries never does, see `missing_watched_keys`.
+    pub fn is_exceeded(&self, change: &ValueChange) -> bool {
+        if !self.matches(&change.key) {
+            return false;
+        }
+        let Some(relative) = change.relative() else {
+            return false;
+        };
+        let percent = relative * 100.0;
+        match self.direction {
+            Direction::Increase => percent > self.percent,
+            Direction::Decrease => -percent > self.percent,
+        }
+    }
+}
+
+/// Parses `KEY=PERCENT`, e.g. `samples_per_second=2`.
+pub fn parse_threshold(s: &str, direction: Direction) -> Result<Threshold, String> {
+    let (key, percent) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("Invalid threshold: {}. Use KEY=PERCENT.", s))?;
+    let percent = percent
+        .trim()
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid threshold percent: {}", percent))?;
+    Ok(Threshold::new(key.trim(), direction, percent))
+}
+
+/// A change exceeding its threshold.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Regression {
+    pub change: ValueChange,
+    pub threshold: Threshold,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.threshold.direction {
+            Direction::Increase => "increase",
+            Direction::Decrease => "decrease",
+        };
+        write!(
+            f,
+            "{}, more than the allowed {}% {}",
+            self.change, self.threshold.percent, direction
+        )
+    }
+}
+
+/// Thresholds matching no key of either summary, e.g. because of a typo,
+/// which would otherwise never report a regression.
+pub fn unknown_thresholds<'a>(
+    old: &Dict,
+    new: &Dict,
+    thresholds: &'a [Threshold],
+) -> Vec<&'a Threshold> {
+    let (old, new) = (flatten_dict(old), flatten_dict(new));
+    thresholds
+        .iter()
+        .filter(|t| !old.keys().chain(new.keys()).any(|key| t.matches(key)))
+        .collect()
+}
+
+pub fn regressions(changes: &[ValueChange], thresholds: &[Threshold]) -> Vec<Regression> {
+    changes
+        .iter()
+        .flat_map(|change| {
+            thresholds
+                .iter()
+                .filter(|t| t.is_exceeded(change))
+                .map(|t| Regression {
+                    change: change.clone(),
+                    threshold: t.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Changes of keys watched by a threshold that are missing from one of the
+/// summaries, and so can not be compared against it.
+pub fn missing_watched_keys<'a>(
+    changes: &'a [ValueChange],
+    thresholds: &[Threshold],
+) -> Vec<&'a ValueChange> {
+    changes
+        .iter()
+        .filter(|change| change.old.is_none() || change.new.is_none())
+        .filter(|change| thresholds.iter().any(|t| t.matches(&change.key)))
+        .collect()
+}
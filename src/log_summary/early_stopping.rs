@@ -0,0 +1,130 @@
+use crate::log_summary::model::{Entry, SectionEntry};
+use serde::Serialize;
+
+/// Normalized key of the `Early Stopping Result:` line in the results section.
+pub const EARLY_STOPPING_KEY: &str = "early_stopping_result";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PercentileEstimate {
+    pub percentile: f64,
+    pub latency_ns: u64,
+}
+
+/// Lines printed by loadgen under `Early Stopping Result:`.
+///
+/// ```text
+/// Early Stopping Result:
+///  * Processed at least 64 queries (193472).
+///  * Would discard 18953 highest latency queries.
+///  * Early stopping 90th percentile estimate: 3214545
+/// ```
+///
+/// Lines that are not recognized are kept verbatim in `notes`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EarlyStoppingResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_successful: Option<bool>,
+    /// Minimum number of queries early stopping needs (`at least N`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_queries: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processed_queries: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discarded_queries: Option<u64>,
+    /// Queries loadgen says it would still need (`... would need ... at least N ...`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_queries: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub estimates: Vec<PercentileEstimate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl EarlyStoppingResult {
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut result = EarlyStoppingResult::default();
+
+        for line in lines {
+            let text = line.trim().trim_start_matches('*').trim();
+            let text = text.strip_suffix('.').unwrap_or(text);
+
+            if text == "Run successful" {
+                result.run_successful = Some(true);
+            } else if text == "Run unsuccessful" {
+                result.run_successful = Some(false);
+            } else if let Some(rest) = text.strip_prefix("Processed at least ") {
+                // "64 queries (193472)"
+                result.min_queries = first_number(rest);
+                result.processed_queries = rest
+                    .split_once('(')
+                    .and_then(|(_, count)| first_number(count));
+            } else if let Some(rest) = text.strip_prefix("Processed ") {
+                result.processed_queries = first_number(rest);
+            } else if let Some(rest) = text.strip_prefix("Would discard ") {
+                result.discarded_queries = first_number(rest);
+            } else if let Some(estimate) = parse_estimate(text) {
+                result.estimates.push(estimate);
+            } else if let Some((_, rest)) = text.split_once("at least ") {
+                // "Not enough queries processed for early stopping estimate
+                //  (would need to process at least 662 total queries)"
+                result.required_queries = first_number(rest);
+            } else {
+                result.notes.push(text.to_string());
+            }
+        }
+
+        result
+    }
+
+    /// Builds the record from the `Early Stopping Result:` entry of a section.
+    pub fn from_entry(entry: &SectionEntry) -> Option<Self> {
+        match &entry.entry {
+            Entry::KeyValue(kv) if kv.normalize_snakecase_key() == EARLY_STOPPING_KEY => {
+                let lines: Vec<String> = entry.children.iter().map(line_of).collect();
+                Some(Self::parse(lines.iter().map(|l| l.as_str())))
+            }
+            _ => None,
+        }
+    }
+
+    /// The highest latency loadgen estimates for the run, i.e. the bound
+    /// early stopping reports for the largest percentile it printed.
+    pub fn latency_bound_ns(&self) -> Option<u64> {
+        self.estimates
+            .iter()
+            .max_by(|a, b| a.percentile.total_cmp(&b.percentile))
+            .map(|e| e.latency_ns)
+    }
+}
+
+fn line_of(entry: &SectionEntry) -> String {
+    match &entry.entry {
+        Entry::KeyValue(kv) => match &kv.value {
+            Some(value) => format!("{}: {}", kv.key, value),
+            None => kv.key.clone(),
+        },
+        Entry::Message(m) => m.message.clone(),
+    }
+}
+
+fn first_number(text: &str) -> Option<u64> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .find(|s| !s.is_empty())
+        .and_then(|s| s.parse().ok())
+}
+
+/// `Early stopping 90th percentile estimate: 3214545`
+fn parse_estimate(text: &str) -> Option<PercentileEstimate> {
+    let rest = text.strip_prefix("Early stopping ")?;
+    let (label, latency) = rest.split_once(':')?;
+    let percentile = label
+        .strip_suffix(" percentile estimate")?
+        .trim()
+        .trim_end_matches("th")
+        .parse()
+        .ok()?;
+    Some(PercentileEstimate {
+        percentile,
+        latency_ns: latency.trim().parse().ok()?,
+    })
+}
@@ -7,12 +7,16 @@ pub type Spanned<Tok, Loc, Error> = Result<(Loc, Tok, Loc), Error>;
 pub enum LexicalError {
     #[default]
     InvalidToken,
+    UnexpectedInput(SourceLocation),
 }
 
 pub fn remove_last_char(s: &str) -> String {
     s[..s.len() - 1].to_string()
 }
 
+/// Position of a token in the summary log.
+///
+/// `line` and `column` are 1-based, `source` is the text of the whole line.
 #[derive(PartialEq, Debug, Clone)]
 pub struct SourceLocation {
     pub source: String,
@@ -20,6 +24,29 @@ pub struct SourceLocation {
     pub column: usize,
 }
 
+impl SourceLocation {
+    /// Locates the byte `offset` of `input`.
+    pub fn at(input: &str, offset: usize) -> Self {
+        let offset = offset.min(input.len());
+        let line_start = input[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |pos| offset + pos);
+        SourceLocation {
+            source: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            line: input[..offset].matches('\n').count() + 1,
+            column: column_of(input, offset),
+        }
+    }
+}
+
+fn column_of(input: &str, offset: usize) -> usize {
+    let line_start = input[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+    input[line_start..offset].chars().count() + 1
+}
+
 #[derive(Clone, Debug, Logos, PartialEq)]
 #[logos(error = LexicalError)]
 #[logos(extras = usize)] // 줄 번호만 추적
@@ -29,7 +56,7 @@ pub enum Token {
         let location = SourceLocation {
             source: remove_last_char(lex.slice()),
             line: lex.extras,
-            column: lex.span().start,
+            column: column_of(lex.source(), lex.span().start),
         };
         location
     }, priority = 4)]
@@ -40,7 +67,7 @@ pub enum Token {
         let location = SourceLocation {
             source: remove_last_char(lex.slice()),
             line: lex.extras,
-            column: lex.span().start,
+            column: column_of(lex.source(), lex.span().start),
         };
         location
     }, priority = 2)]
@@ -52,7 +79,7 @@ pub enum Token {
         let location = SourceLocation {
             source: remove_last_char(lex.slice()),
             line: lex.extras,
-            column: lex.span().start,
+            column: column_of(lex.source(), lex.span().start),
         };
         location
     }, priority = 1)]
@@ -61,7 +88,20 @@ pub enum Token {
     EOF,
 }
 
+impl Token {
+    /// Name of the token kind, as used by the grammar.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Token::SectionSeparatorLine(_) => "SectionSeparatorLine",
+            Token::Line(_) => "Line",
+            Token::EndOfSection(_) => "EndOfSection",
+            Token::EOF => "EOF",
+        }
+    }
+}
+
 pub struct Lexer<'input> {
+    input: &'input str,
     eof_encountered: bool,
     line_number: usize,
     token_stream: SpannedIter<'input, Token>,
@@ -76,6 +116,7 @@ impl<'input> Lexer<'input> {
         let token_stream = lexer.spanned();
 
         Lexer {
+            input,
             eof_encountered: false,
             line_number: 0,
             token_stream,
@@ -92,7 +133,15 @@ impl Iterator for Lexer<'_> {
         }
         if let Some((token, span)) = self.token_stream.next() {
             self.line_number = self.token_stream.extras;
-            let token = token.ok()?;
+            let token = match token {
+                Ok(token) => token,
+                Err(_) => {
+                    self.eof_encountered = true;
+                    return Some(Err(LexicalError::UnexpectedInput(SourceLocation::at(
+                        self.input, span.start,
+                    ))));
+                }
+            };
             debug!(
                 "TOKEN: {:?}, start: {}, end: {}, line: {}",
                 token,
@@ -104,7 +153,8 @@ impl Iterator for Lexer<'_> {
         } else {
             debug!("TOKEN EOF");
             self.eof_encountered = true;
-            Some(Ok((0, Token::EOF, 0)))
+            let end = self.input.len();
+            Some(Ok((end, Token::EOF, end)))
         }
     }
 }
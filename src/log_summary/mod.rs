@@ -1,85 +1,190 @@
+pub mod diff;
+pub mod early_stopping;
 pub mod grammar;
 pub mod lexer;
 pub mod model;
+pub mod report;
+pub mod stats;
+pub mod summary;
 
+use crate::error::ParseError;
+use crate::format::cell;
+use crate::table::Table;
 use grammar::LogSummaryParser;
-use lexer::Lexer;
-use model::Document;
+use lexer::{Lexer, LexicalError, SourceLocation, Token};
+use log::warn;
+use model::{flatten_dict, Dict, DictConversion, DictOptions, Document, DuplicateKey};
 use serde_value::Value;
 use std::fs;
 use std::io;
 
-pub fn parse_log_summary(log_summary: &str) -> Result<Document, String> {
+pub fn parse_log_summary(log_summary: &str) -> Result<Document, ParseError> {
     let lexer = Lexer::new(log_summary);
     let parser = LogSummaryParser::new();
-    match parser.parse(lexer) {
-        Ok(doc) => Ok(doc),
-        Err(e) => {
-            // 에러가 발생한 위치를 찾기 위해 각 라인의 길이를 계산
-            let mut current_pos = 0;
-            let mut line_number = 1;
-
-            // 에러 메시지에서 위치 정보 추출
-            if let Some(location) = format!("{:?}", e).find("location: ") {
-                if let Ok(error_pos) = format!("{:?}", e)[location + 10..]
-                    .split_whitespace()
-                    .next()
-                    .unwrap_or("0")
-                    .parse::<usize>()
-                {
-                    // 에러 위치까지 라인 수와 단어 위치 계산
-                    let mut line_content = "";
-                    for line in log_summary.lines() {
-                        if current_pos + line.len() + 1 > error_pos {
-                            line_content = line;
-                            break;
-                        }
-                        current_pos += line.len() + 1; // +1 for newline
-                        line_number += 1;
-                    }
-
-                    // 라인에서의 단어 위치 계산
-                    let word_pos = error_pos - current_pos;
-                    let word = line_content
-                        .split_whitespace()
-                        .nth(word_pos / 2)
-                        .unwrap_or("<unknown>");
-
-                    Err(format!(
-                        "Parse error at line {}, word '{}' (position {}): {:?}",
-                        line_number, word, word_pos, e
-                    ))
-                } else {
-                    Err(format!("Parse error: {:?}", e))
-                }
-            } else {
-                Err(format!("Parse error: {:?}", e))
-            }
+    parser
+        .parse(lexer)
+        .map_err(|e| summary_parse_error(log_summary, e))
+}
+
+fn summary_parse_error(
+    log_summary: &str,
+    e: lalrpop_util::ParseError<usize, Token, LexicalError>,
+) -> ParseError {
+    use lalrpop_util::ParseError as LalrpopError;
+
+    // 파일 끝 에러는 마지막 내용이 있는 줄의 끝을 가리키도록 함
+    let eof_location = || SourceLocation::at(log_summary, log_summary.trim_end().len());
+
+    let lexical = |location: SourceLocation| ParseError::Lexical {
+        path: None,
+        at_eof: !log_summary.ends_with('\n') && location.line == log_summary.lines().count(),
+        location,
+    };
+
+    match e {
+        LalrpopError::InvalidToken { location } => {
+            lexical(SourceLocation::at(log_summary, location))
         }
+        LalrpopError::User {
+            error: LexicalError::UnexpectedInput(location),
+        } => lexical(location),
+        LalrpopError::User {
+            error: LexicalError::InvalidToken,
+        } => lexical(eof_location()),
+        LalrpopError::UnrecognizedEof { expected, .. }
+        | LalrpopError::UnrecognizedToken {
+            token: (_, Token::EOF, _),
+            expected,
+        } => ParseError::UnexpectedEof {
+            path: None,
+            location: eof_location(),
+            expected,
+        },
+        LalrpopError::UnrecognizedToken {
+            token: (start, token, _),
+            expected,
+        } => ParseError::UnexpectedToken {
+            path: None,
+            location: SourceLocation::at(log_summary, start),
+            previous: previous_line(log_summary, start),
+            token: token.kind().to_string(),
+            expected,
+        },
+        LalrpopError::ExtraToken {
+            token: (start, token, _),
+        } => ParseError::UnexpectedToken {
+            path: None,
+            location: SourceLocation::at(log_summary, start),
+            previous: previous_line(log_summary, start),
+            token: token.kind().to_string(),
+            expected: vec![],
+        },
     }
 }
 
-pub fn parse_mlperf_results_file(input_file: &str) -> io::Result<Value> {
-    let mut content = fs::read_to_string(input_file)?;
+/// The closest non-blank line before the line containing `offset`.
+fn previous_line(log_summary: &str, offset: usize) -> Option<Box<SourceLocation>> {
+    let line_start = log_summary[..offset].rfind('\n')?;
+    let before = log_summary[..line_start].trim_end();
+    if before.is_empty() {
+        None
+    } else {
+        Some(Box::new(SourceLocation::at(log_summary, before.len())))
+    }
+}
+
+pub fn parse_log_summary_file(input_file: &str) -> Result<Document, ParseError> {
+    let mut content =
+        fs::read_to_string(input_file).map_err(|e| ParseError::from(e).with_path(input_file))?;
     if !content.ends_with("\n\n") {
         content.push_str("\n\n");
     }
-    match parse_log_summary(&content) {
-        Ok(doc) => Ok(serde_value::to_value(doc.to_dict()).unwrap()),
-        Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+    parse_log_summary(&content).map_err(|e| e.with_path(input_file))
+}
+
+/// Parses a summary file into a `Value` map. Its keys are sorted; use
+/// `parse_mlperf_results_dict` to keep them in the order of the log.
+pub fn parse_mlperf_results_file(input_file: &str) -> Result<Value, ParseError> {
+    Ok(serde_value::to_value(parse_mlperf_results_dict(input_file)?).unwrap())
+}
+
+/// Parses a summary file into its dict, keeping sections and keys in the
+/// order they appear in the log.
+pub fn parse_mlperf_results_dict(input_file: &str) -> Result<Dict, ParseError> {
+    Ok(parse_log_summary_file(input_file)?.to_dict())
+}
+
+/// Parses a summary file into its dict, resolving duplicated keys with `options`.
+pub fn parse_log_summary_dict(
+    input_file: &str,
+    options: &DictOptions,
+) -> Result<DictConversion, ParseError> {
+    let doc = parse_log_summary_file(input_file)?;
+    to_dict_with(&doc, input_file, options)
+}
+
+fn to_dict_with(
+    doc: &Document,
+    input_file: &str,
+    options: &DictOptions,
+) -> Result<DictConversion, ParseError> {
+    doc.to_dict_with(options)
+        .map_err(|duplicate| ParseError::DuplicateKey {
+            path: Some(input_file.into()),
+            duplicate,
+        })
+}
+
+fn write_summary<W: io::Write>(summary: &Dict, writer: &mut W, format: &str) -> io::Result<()> {
+    match format {
+        "json" => serde_json::to_writer_pretty(writer, summary)?,
+        "yaml" => serde_yaml::to_writer(writer, summary).map_err(io::Error::other)?,
+        "csv" => {
+            let mut table = Table::new(vec!["key".to_string(), "value".to_string()]);
+            for (key, value) in flatten_dict(summary) {
+                table.rows.push(vec![key, cell(&value)]);
+            }
+            table.write_csv(writer)?
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid format. Use 'json', 'yaml', 'csv', 'markdown' or 'html'.",
+            ))
+        }
     }
+    Ok(())
 }
 
 pub fn save_summary_as_json<W: io::Write>(input_file: &str, writer: &mut W) -> io::Result<()> {
-    let summary = parse_mlperf_results_file(input_file)?;
-    serde_json::to_writer_pretty(writer, &summary)?;
-    Ok(())
+    save_summary(input_file, writer, "json")
 }
 
 pub fn save_summary_as_yaml<W: io::Write>(input_file: &str, writer: &mut W) -> io::Result<()> {
-    let summary = parse_mlperf_results_file(input_file)?;
-    serde_yaml::to_writer(writer, &summary).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-    Ok(())
+    save_summary(input_file, writer, "yaml")
+}
+
+/// Writes `key,value` rows with the flattened dotted keys of the summary.
+pub fn save_summary_as_csv<W: io::Write>(input_file: &str, writer: &mut W) -> io::Result<()> {
+    save_summary(input_file, writer, "csv")
+}
+
+/// Like `save_summary`, returning the duplicated keys found instead of logging them.
+pub fn save_summary_with<W: io::Write>(
+    input_file: &str,
+    writer: &mut W,
+    format: &str,
+    options: &DictOptions,
+) -> io::Result<Vec<DuplicateKey>> {
+    let doc = parse_log_summary_file(input_file)?;
+    let conversion = to_dict_with(&doc, input_file, options)?;
+    match format {
+        // reports are rendered from the log's sections rather than the dict
+        "markdown" | "md" => report::write_markdown(&doc, writer)?,
+        "html" => report::write_html(&doc, writer)?,
+        _ => write_summary(&conversion.dict, writer, format)?,
+    }
+    Ok(conversion.warnings)
 }
 
 pub fn save_summary<W: io::Write>(
@@ -87,12 +192,9 @@ pub fn save_summary<W: io::Write>(
     writer: &mut W,
     format: &str,
 ) -> io::Result<()> {
-    match format {
-        "json" => save_summary_as_json(input_file, writer),
-        "yaml" => save_summary_as_yaml(input_file, writer),
-        _ => Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "Invalid format. Use 'json' or 'yaml'.",
-        )),
+    let warnings = save_summary_with(input_file, writer, format, &DictOptions::default())?;
+    for warning in warnings {
+        warn!("{}", warning);
     }
+    Ok(())
 }
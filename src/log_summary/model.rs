@@ -1,9 +1,92 @@
-use log::debug;
+use crate::log_summary::early_stopping::EarlyStoppingResult;
+use indexmap::IndexMap;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use serde_value::Value;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::fmt;
 use std::rc::Rc;
+use std::str::FromStr;
+
+/// Dict produced by `Document::to_dict`, keeping keys in the order they
+/// appear in the log so serialized output is stable across runs.
+pub type Dict = IndexMap<String, DictValue>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum DictValue {
+    Value(Value),
+    Dict(Dict),
+    List(Vec<DictValue>),
+}
+
+impl From<Value> for DictValue {
+    fn from(value: Value) -> Self {
+        DictValue::Value(value)
+    }
+}
+
+impl From<Dict> for DictValue {
+    fn from(dict: Dict) -> Self {
+        DictValue::Dict(dict)
+    }
+}
+
+/// Converts structured records (serialized through `serde_json`, which keeps
+/// field order) into dict values.
+impl From<serde_json::Value> for DictValue {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => DictValue::Value(Value::Unit),
+            serde_json::Value::Bool(b) => DictValue::Value(Value::Bool(b)),
+            serde_json::Value::Number(n) => DictValue::Value(if let Some(u) = n.as_u64() {
+                Value::U64(u)
+            } else if let Some(i) = n.as_i64() {
+                Value::I64(i)
+            } else {
+                Value::F64(n.as_f64().unwrap_or_default())
+            }),
+            serde_json::Value::String(s) => DictValue::Value(Value::String(s)),
+            serde_json::Value::Array(items) => {
+                DictValue::List(items.into_iter().map(DictValue::from).collect())
+            }
+            serde_json::Value::Object(map) => DictValue::Dict(
+                map.into_iter()
+                    .map(|(k, v)| (k, DictValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Flattens a dict into dotted keys, e.g.
+/// `mlperf_results_summary.result_is.details.min_duration_satisfied`.
+/// List items are keyed by their index (`note.0`).
+pub fn flatten_dict(dict: &Dict) -> IndexMap<String, Value> {
+    fn flatten_into(prefix: &str, value: &DictValue, flat: &mut IndexMap<String, Value>) {
+        match value {
+            DictValue::Value(v) => {
+                flat.insert(prefix.to_string(), v.clone());
+            }
+            DictValue::Dict(d) => {
+                for (key, value) in d {
+                    flatten_into(&child_scope(prefix, key), value, flat);
+                }
+            }
+            DictValue::List(items) => {
+                for (i, value) in items.iter().enumerate() {
+                    flatten_into(&child_scope(prefix, &i.to_string()), value, flat);
+                }
+            }
+        }
+    }
+
+    let mut flat = IndexMap::new();
+    for (key, value) in dict {
+        flatten_into(key, value, &mut flat);
+    }
+    flat
+}
 
 #[derive(Debug, Clone)]
 pub struct RcSectionEntry {
@@ -75,23 +158,239 @@ pub fn build_structure_by_priority(entries: Vec<Message>) -> Vec<SectionEntry> {
     RcSectionEntryVec(result).into()
 }
 
-fn reduce_dict(
-    result_dict: Vec<HashMap<String, serde_value::Value>>,
-) -> HashMap<String, serde_value::Value> {
-    let mut merged_dict = HashMap::new();
-    for d in result_dict {
+/// What to do when a dict receives the same normalized key twice, e.g. two
+/// `Result is` lines or `target_latency (ns)` next to `Target latency (ns)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    Error,
+    KeepFirst,
+    #[default]
+    KeepLast,
+    /// Turn the value into a list holding every occurrence.
+    Collect,
+}
+
+impl FromStr for DuplicateKeyPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(DuplicateKeyPolicy::Error),
+            "first" => Ok(DuplicateKeyPolicy::KeepFirst),
+            "last" => Ok(DuplicateKeyPolicy::KeepLast),
+            "list" => Ok(DuplicateKeyPolicy::Collect),
+            _ => Err(format!(
+                "Invalid duplicate key policy: {}. Use 'error', 'first', 'last' or 'list'.",
+                s
+            )),
+        }
+    }
+}
+
+/// Time unit found in a key suffix such as `Min latency (ns)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    Ns,
+    Us,
+    Ms,
+    S,
+}
+
+impl TimeUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeUnit::Ns => "ns",
+            TimeUnit::Us => "us",
+            TimeUnit::Ms => "ms",
+            TimeUnit::S => "s",
+        }
+    }
+
+    fn nanos(&self) -> u64 {
+        match self {
+            TimeUnit::Ns => 1,
+            TimeUnit::Us => 1_000,
+            TimeUnit::Ms => 1_000_000,
+            TimeUnit::S => 1_000_000_000,
+        }
+    }
+
+    /// Converts `value` from `self` to `to`. Integers stay integers as long
+    /// as the conversion is exact, anything else becomes an `F64`.
+    pub fn convert(&self, value: &Value, to: TimeUnit) -> Option<Value> {
+        let (from, to) = (self.nanos(), to.nanos());
+        let exact = |n: u64| (from % to == 0).then(|| n.checked_mul(from / to)).flatten();
+        let scaled = |f: f64| Value::F64(f * from as f64 / to as f64);
+        match value {
+            Value::U64(n) => Some(exact(*n).map_or_else(|| scaled(*n as f64), Value::U64)),
+            Value::I64(n) => Some(
+                exact(n.unsigned_abs())
+                    .and_then(|m| i64::try_from(m).ok())
+                    .map_or_else(|| scaled(*n as f64), |m| Value::I64(m * n.signum())),
+            ),
+            Value::F32(f) => Some(scaled(*f as f64)),
+            Value::F64(f) => Some(scaled(*f)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TimeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ns" => Ok(TimeUnit::Ns),
+            "us" => Ok(TimeUnit::Us),
+            "ms" => Ok(TimeUnit::Ms),
+            "s" => Ok(TimeUnit::S),
+            _ => Err(format!("Unknown time unit: {}", s)),
+        }
+    }
+}
+
+/// How values of keys with a unit suffix (`(ns)`, `(ms)`, ...) and
+/// percentages (`99%`) end up in the dict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitMode {
+    /// `min_latency_ns: 123456`, `quality_target: "99%"`.
+    #[default]
+    Strip,
+    /// `min_latency: {"value": 123456, "unit": "ns"}`, `quality_target: 0.99`.
+    Annotate,
+    /// Every time converted to the given unit, `min_latency_ms: 0.123456`,
+    /// `quality_target: 0.99`.
+    Normalize(TimeUnit),
+}
+
+impl FromStr for UnitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strip" => Ok(UnitMode::Strip),
+            "annotate" => Ok(UnitMode::Annotate),
+            _ => s.parse().map(UnitMode::Normalize).map_err(|_| {
+                format!(
+                    "Invalid unit mode: {}. Use 'strip', 'annotate', 'ns', 'us', 'ms' or 's'.",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DictOptions {
+    pub duplicate_keys: DuplicateKeyPolicy,
+    pub units: UnitMode,
+}
+
+/// A key found more than once in the same dict. `scope` is the dotted path
+/// of the dict (`mlperf_results_summary.result_is.details`), empty for the
+/// document itself.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateKey {
+    pub scope: String,
+    pub key: String,
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scope.is_empty() {
+            write!(f, "duplicate key '{}'", self.key)
+        } else {
+            write!(f, "duplicate key '{}' in '{}'", self.key, self.scope)
+        }
+    }
+}
+
+/// Result of `Document::to_dict_with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictConversion {
+    pub dict: Dict,
+    /// Duplicated keys resolved according to the policy.
+    pub warnings: Vec<DuplicateKey>,
+}
+
+struct DictBuilder<'a> {
+    options: &'a DictOptions,
+    warnings: Vec<DuplicateKey>,
+}
+
+impl DictBuilder<'_> {
+    /// Merges a single entry dict into `merged_dict`. Notes are never treated
+    /// as duplicates: with `notes_as_list` they always end up in a list,
+    /// otherwise only once a second note shows up.
+    fn merge(
+        &mut self,
+        merged_dict: &mut Dict,
+        scope: &str,
+        d: Dict,
+        notes_as_list: bool,
+    ) -> Result<(), DuplicateKey> {
         for (key, value) in d {
             if key == "note" {
-                let notes = merged_dict.entry(key).or_insert_with(|| Value::Seq(vec![]));
-                if let Value::Seq(arr) = notes {
-                    arr.push(value);
+                match merged_dict.get_mut(&key) {
+                    Some(DictValue::List(arr)) if notes_as_list => arr.push(value),
+                    Some(existing) => push_value(existing, value),
+                    None if notes_as_list => {
+                        merged_dict.insert(key, DictValue::List(vec![value]));
+                    }
+                    None => {
+                        merged_dict.insert(key, value);
+                    }
                 }
-            } else {
-                merged_dict.insert(key, value);
+                continue;
             }
+
+            let existing = match merged_dict.get_mut(&key) {
+                Some(existing) => existing,
+                None => {
+                    merged_dict.insert(key, value);
+                    continue;
+                }
+            };
+
+            let duplicate = DuplicateKey {
+                scope: scope.to_string(),
+                key,
+            };
+            match self.options.duplicate_keys {
+                DuplicateKeyPolicy::Error => return Err(duplicate),
+                DuplicateKeyPolicy::KeepFirst => {}
+                DuplicateKeyPolicy::KeepLast => *existing = value,
+                DuplicateKeyPolicy::Collect => push_value(existing, value),
+            }
+            self.warnings.push(duplicate);
+        }
+        Ok(())
+    }
+}
+
+/// Appends `value` to `existing`, turning `existing` into a list first.
+fn push_value(existing: &mut DictValue, value: DictValue) {
+    match existing {
+        DictValue::List(arr) => arr.push(value),
+        _ => {
+            let first = std::mem::replace(existing, DictValue::List(vec![]));
+            *existing = DictValue::List(vec![first, value]);
         }
     }
-    merged_dict
+}
+
+fn child_scope(scope: &str, key: &str) -> String {
+    if scope.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", scope, key)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,12 +412,9 @@ pub enum Entry {
 }
 
 impl Entry {
-    fn to_dict(
-        &self,
-        details: Option<HashMap<String, serde_value::Value>>,
-    ) -> HashMap<String, serde_value::Value> {
+    fn to_dict(&self, details: Option<Dict>, units: UnitMode) -> Dict {
         match self {
-            Entry::KeyValue(k) => k.to_dict(details),
+            Entry::KeyValue(k) => k.to_dict(details, units),
             Entry::Message(m) => m.to_dict(details),
         }
     }
@@ -163,17 +459,31 @@ pub struct SectionEntry {
 }
 
 impl SectionEntry {
-    fn to_dict(&self) -> HashMap<String, serde_value::Value> {
-        if self.children.len() > 0 {
-            let details = reduce_dict(
-                self.children
-                    .iter()
-                    .map(|child: &SectionEntry| child.to_dict())
-                    .collect(),
-            );
-            self.entry.to_dict(Some(details))
+    fn to_dict(&self, builder: &mut DictBuilder, scope: &str) -> Result<Dict, DuplicateKey> {
+        if let Some(early_stopping) = EarlyStoppingResult::from_entry(self) {
+            if let Entry::KeyValue(k) = &self.entry {
+                return Ok(Dict::from([(
+                    k.normalize_snakecase_key(),
+                    serde_json::to_value(early_stopping).unwrap().into(),
+                )]));
+            }
+        }
+
+        let units = builder.options.units;
+        if !self.children.is_empty() {
+            let key = match &self.entry {
+                Entry::KeyValue(k) => k.dict_key(units),
+                Entry::Message(_) => "note".to_string(),
+            };
+            let details_scope = child_scope(&child_scope(scope, &key), "details");
+            let mut details = Dict::new();
+            for child in &self.children {
+                let child_dict = child.to_dict(builder, &details_scope)?;
+                builder.merge(&mut details, &details_scope, child_dict, true)?;
+            }
+            Ok(self.entry.to_dict(Some(details), units))
         } else {
-            self.entry.to_dict(None)
+            Ok(self.entry.to_dict(None, units))
         }
     }
 }
@@ -186,7 +496,7 @@ pub struct KeyValueEntry {
 }
 
 impl KeyValueEntry {
-    fn normalize_snakecase_key(&self) -> String {
+    pub(crate) fn normalize_snakecase_key(&self) -> String {
         self.key
             .trim()
             .to_lowercase()
@@ -196,6 +506,64 @@ impl KeyValueEntry {
             .replace(")", "")
     }
 
+    /// Unit in the parenthesized key suffix, `Min latency (ns)` -> `ns`.
+    pub fn unit(&self) -> Option<TimeUnit> {
+        let (_, suffix) = self.key.trim().strip_suffix(')')?.rsplit_once('(')?;
+        suffix.trim().parse().ok()
+    }
+
+    /// Key of the entry in the dict for the given unit mode.
+    fn dict_key(&self, units: UnitMode) -> String {
+        match (units, self.unit()) {
+            (UnitMode::Strip, _) | (_, None) => self.normalize_snakecase_key(),
+            (UnitMode::Annotate, Some(_)) => self.base_key(),
+            (UnitMode::Normalize(to), Some(_)) => format!("{}_{}", self.base_key(), to),
+        }
+    }
+
+    /// Normalized key without its unit suffix.
+    fn base_key(&self) -> String {
+        let key = self.key.trim();
+        let key = key.rsplit_once('(').map_or(key, |(base, _)| base);
+        KeyValueEntry {
+            key: key.to_string(),
+            value: None,
+            indent_level: self.indent_level,
+        }
+        .normalize_snakecase_key()
+    }
+
+    /// `99%` -> `0.99`
+    fn parse_percentage(&self) -> Option<Value> {
+        let percent = self.value.as_ref()?.trim().strip_suffix('%')?;
+        percent
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|p| Value::F64(p / 100.0))
+    }
+
+    fn parse_value_with(&self, units: UnitMode) -> DictValue {
+        if units == UnitMode::Strip {
+            return self.parse_value().into();
+        }
+        if let Some(fraction) = self.parse_percentage() {
+            return fraction.into();
+        }
+        let value = self.parse_value();
+        match (units, self.unit()) {
+            (UnitMode::Annotate, Some(unit)) => Dict::from([
+                ("value".to_string(), value.into()),
+                ("unit".to_string(), Value::String(unit.to_string()).into()),
+            ])
+            .into(),
+            (UnitMode::Normalize(to), Some(unit)) => {
+                unit.convert(&value, to).unwrap_or(value).into()
+            }
+            _ => value.into(),
+        }
+    }
+
     fn parse_value(&self) -> serde_value::Value {
         debug!("         value: {:?}", self.value);
         match &self.value {
@@ -206,14 +574,10 @@ impl KeyValueEntry {
                     Value::Bool(true)
                 } else if low_value == "no" {
                     Value::Bool(false)
-                } else if let Ok(num) = low_value.parse::<i64>() {
-                    Value::I64(num)
-                } else if let Ok(num) = low_value.parse::<u64>() {
-                    Value::U64(num)
-                } else if let Ok(f) = low_value.parse::<f32>() {
-                    Value::F32(f)
-                } else if let Ok(f) = low_value.parse::<f64>() {
-                    Value::F64(f)
+                } else if let Some(num) =
+                    parse_number(&low_value, number_type(&self.normalize_snakecase_key()))
+                {
+                    num
                 } else {
                     Value::String(v.clone())
                 }
@@ -221,29 +585,124 @@ impl KeyValueEntry {
         }
     }
 
-    fn to_dict(
-        &self,
-        details: Option<HashMap<String, serde_value::Value>>,
-    ) -> HashMap<String, serde_value::Value> {
-        let key = self.normalize_snakecase_key();
-        let value = self.parse_value();
+    fn to_dict(&self, details: Option<Dict>, units: UnitMode) -> Dict {
+        let key = self.dict_key(units);
+        let value = self.parse_value_with(units);
         if let Some(details) = details {
-            let value_map: HashMap<String, Value> = HashMap::from([
+            let value_map = Dict::from([
                 ("value".to_string(), value),
-                (
-                    "details".to_string(),
-                    serde_value::to_value(details).unwrap(),
-                ),
+                ("details".to_string(), details.into()),
             ]);
 
             // {"key": {"value": value, "details": details}}
-            HashMap::from([(key, serde_value::to_value(value_map).unwrap())])
+            Dict::from([(key, value_map.into())])
         } else {
-            HashMap::from([(key, value)])
+            Dict::from([(key, value)])
         }
     }
 }
 
+/// Numeric type a summary value is stored as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberType {
+    /// `U64`, e.g. RNG seeds which use the full 64-bit range.
+    Unsigned,
+    /// `I64`, e.g. latencies, durations and counts.
+    Signed,
+    /// `F64`, e.g. throughputs and probabilities.
+    Float,
+}
+
+/// Keys loadgen prints in the summary whose values have a fixed type, as
+/// normalized by `normalize_snakecase_key`.
+const NUMBER_TYPES: &[(&str, NumberType)] = &[
+    // results
+    ("samples_per_second", NumberType::Float),
+    ("tokens_per_second", NumberType::Float),
+    ("tokens_per_second_inferred", NumberType::Float),
+    ("completed_samples_per_second", NumberType::Float),
+    ("completed_tokens_per_second", NumberType::Float),
+    ("scheduled_samples_per_second", NumberType::Float),
+    ("90th_percentile_latency_ns", NumberType::Signed),
+    ("99th_percentile_latency_ns", NumberType::Signed),
+    // additional stats
+    ("qps_w/_loadgen_overhead", NumberType::Float),
+    ("qps_w/o_loadgen_overhead", NumberType::Float),
+    ("min_latency_ns", NumberType::Signed),
+    ("max_latency_ns", NumberType::Signed),
+    ("mean_latency_ns", NumberType::Signed),
+    ("min_first_token_latency_ns", NumberType::Signed),
+    ("max_first_token_latency_ns", NumberType::Signed),
+    ("mean_first_token_latency_ns", NumberType::Signed),
+    ("min_time_to_output_token_ns", NumberType::Signed),
+    ("max_time_to_output_token_ns", NumberType::Signed),
+    ("mean_time_to_output_token_ns", NumberType::Signed),
+    // test parameters
+    ("samples_per_query", NumberType::Signed),
+    ("target_qps", NumberType::Float),
+    ("target_latency_ns", NumberType::Signed),
+    ("max_async_queries", NumberType::Signed),
+    ("min_duration_ms", NumberType::Signed),
+    ("max_duration_ms", NumberType::Signed),
+    ("min_query_count", NumberType::Signed),
+    ("max_query_count", NumberType::Signed),
+    ("qsl_rng_seed", NumberType::Unsigned),
+    ("sample_index_rng_seed", NumberType::Unsigned),
+    ("schedule_rng_seed", NumberType::Unsigned),
+    ("accuracy_log_rng_seed", NumberType::Unsigned),
+    ("accuracy_log_probability", NumberType::Float),
+    ("accuracy_log_sampling_target", NumberType::Signed),
+    ("performance_sample_count", NumberType::Signed),
+];
+
+/// Percentiles of the `Additional Stats` latencies, `50.00 percentile latency (ns)`, ...
+const LATENCY_PERCENTILES: &[&str] = &["50_00", "90_00", "95_00", "97_00", "99_00", "99_90"];
+
+/// Latencies printed for each of `LATENCY_PERCENTILES`.
+const PERCENTILE_LATENCIES: &[&str] = &[
+    "percentile_latency_ns",
+    "percentile_first_token_latency_ns",
+    "percentile_time_to_output_token_ns",
+];
+
+/// Type of the values of a known key, so that the same key has the same
+/// type in every log no matter how its value happens to be printed
+/// (`target_qps : 1000` vs `target_qps : 1000.5`). `None` for keys whose
+/// type is inferred from the value.
+pub fn number_type(key: &str) -> Option<NumberType> {
+    if let Some(&(_, number_type)) = NUMBER_TYPES.iter().find(|(name, _)| *name == key) {
+        return Some(number_type);
+    }
+    let is_percentile = LATENCY_PERCENTILES.iter().any(|percentile| {
+        key.strip_prefix(percentile)
+            .and_then(|latency| latency.strip_prefix('_'))
+            .is_some_and(|latency| PERCENTILE_LATENCIES.contains(&latency))
+    });
+    is_percentile.then_some(NumberType::Signed)
+}
+
+/// Parses a number without going through `f32`. Integers stay exact; a value
+/// that does not fit the key's type (a negative seed, a fractional latency)
+/// falls back to the type inferred from the value.
+fn parse_number(value: &str, number_type: Option<NumberType>) -> Option<Value> {
+    let float = |v: &str| v.parse::<f64>().ok().filter(|f| f.is_finite());
+    let inferred = || {
+        value
+            .parse::<i64>()
+            .map(Value::I64)
+            .or_else(|_| value.parse::<u64>().map(Value::U64))
+            .ok()
+            .or_else(|| float(value).map(Value::F64))
+    };
+    let typed = match number_type {
+        Some(NumberType::Unsigned) => value.parse::<u64>().map(Value::U64).ok(),
+        Some(NumberType::Signed) => value.parse::<i64>().map(Value::I64).ok(),
+        Some(NumberType::Float) => float(value).map(Value::F64),
+        None => None,
+    };
+    typed.or_else(inferred)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageEntry {
     pub message: String,
@@ -251,26 +710,26 @@ pub struct MessageEntry {
 }
 
 impl MessageEntry {
-    fn to_dict(
-        &self,
-        details: Option<HashMap<String, serde_value::Value>>,
-    ) -> HashMap<String, serde_value::Value> {
+    fn to_dict(&self, details: Option<Dict>) -> Dict {
         if let Some(details) = details {
             // {"note": {"value": "message", "details": details}}
-            HashMap::from([(
+            Dict::from([(
                 "note".to_string(),
-                serde_value::to_value(HashMap::from([
-                    ("value".to_string(), Value::String(self.message.clone())),
+                Dict::from([
                     (
-                        "details".to_string(),
-                        serde_value::to_value(details).unwrap(),
+                        "value".to_string(),
+                        Value::String(self.message.clone()).into(),
                     ),
-                ]))
-                .unwrap(),
+                    ("details".to_string(), details.into()),
+                ])
+                .into(),
             )])
         } else {
             // {"note": "message"}
-            HashMap::from([("note".to_string(), Value::String(self.message.clone()))])
+            Dict::from([(
+                "note".to_string(),
+                Value::String(self.message.clone()).into(),
+            )])
         }
     }
 }
@@ -297,22 +756,17 @@ impl SectionTable {
             .replace(")", "")
     }
 
-    fn to_dict(&self) -> HashMap<String, serde_value::Value> {
-        let mut map = HashMap::new();
-        let entry_dicts: Vec<_> = self.entries.iter().map(|entry| entry.to_dict()).collect();
-
-        let merged_dict = entry_dicts
-            .into_iter()
-            .fold(HashMap::new(), |mut acc, map| {
-                acc.extend(map);
-                acc
-            });
+    fn to_dict(&self, builder: &mut DictBuilder) -> Result<Dict, DuplicateKey> {
+        let mut map = Dict::new();
+        let title = self.normalize_title(&self.title);
+        let mut merged_dict = Dict::new();
+        for entry in &self.entries {
+            let entry_dict = entry.to_dict(builder, &title)?;
+            builder.merge(&mut merged_dict, &title, entry_dict, false)?;
+        }
 
-        map.insert(
-            self.normalize_title(&self.title),
-            serde_value::to_value(merged_dict).unwrap(),
-        );
-        map
+        map.insert(title, merged_dict.into());
+        Ok(map)
     }
 }
 
@@ -322,11 +776,11 @@ pub struct SectionNote {
 }
 
 impl SectionNote {
-    fn to_dict(&self) -> HashMap<String, serde_value::Value> {
-        let mut map = HashMap::new();
+    fn to_dict(&self) -> Dict {
+        let mut map = Dict::new();
         map.insert(
             "note".to_string(),
-            serde_value::Value::String(self.message.message.clone()),
+            serde_value::Value::String(self.message.message.clone()).into(),
         );
         map
     }
@@ -338,15 +792,35 @@ pub struct Document {
 }
 
 impl Document {
-    pub fn to_dict(&self) -> HashMap<String, serde_value::Value> {
-        reduce_dict(
-            self.sections
-                .iter()
-                .map(|section| match section {
-                    Section::Table(t) => t.to_dict(),
-                    Section::Note(n) => n.to_dict(),
-                })
-                .collect(),
-        )
+    /// Converts the document into a dict whose sections and keys follow the
+    /// order of the log. Duplicated keys keep the last value and are logged.
+    pub fn to_dict(&self) -> Dict {
+        let conversion = self
+            .to_dict_with(&DictOptions::default())
+            .expect("KeepLast policy never fails");
+        for warning in &conversion.warnings {
+            warn!("{}", warning);
+        }
+        conversion.dict
+    }
+
+    pub fn to_dict_with(&self, options: &DictOptions) -> Result<DictConversion, DuplicateKey> {
+        let mut builder = DictBuilder {
+            options,
+            warnings: Vec::new(),
+        };
+        let mut dict = Dict::new();
+        for section in &self.sections {
+            let section_dict = match section {
+                Section::Table(t) => t.to_dict(&mut builder)?,
+                Section::Note(n) => n.to_dict(),
+            };
+            builder.merge(&mut dict, "", section_dict, true)?;
+        }
+
+        Ok(DictConversion {
+            dict,
+            warnings: builder.warnings,
+        })
     }
 }
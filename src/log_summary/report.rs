@@ -0,0 +1,114 @@
+use super::model::{Document, Entry, Section, SectionEntry};
+use crate::table::Table;
+use std::io;
+
+/// Whether an entry reports a failure: an `INVALID` result or an unsatisfied
+/// constraint such as `Min duration satisfied : NO`.
+fn is_failure(entry: &Entry) -> bool {
+    match entry {
+        Entry::KeyValue(k) => {
+            let value = k.value.as_deref().unwrap_or_default().trim();
+            value.eq_ignore_ascii_case("invalid")
+                || (k.key.to_lowercase().contains("satisfied") && value.eq_ignore_ascii_case("no"))
+        }
+        Entry::Message(_) => false,
+    }
+}
+
+/// Key and value cells of an entry as printed in the log.
+fn cells(entry: &Entry) -> (String, String) {
+    match entry {
+        Entry::KeyValue(k) => (
+            k.key.trim().to_string(),
+            k.value.as_deref().unwrap_or_default().to_string(),
+        ),
+        Entry::Message(m) => (m.message.trim().to_string(), String::new()),
+    }
+}
+
+/// Visits the entries depth first along with their depth below the table.
+fn walk<'a>(entries: &'a [SectionEntry], depth: usize, f: &mut impl FnMut(&'a Entry, usize)) {
+    for entry in entries {
+        f(&entry.entry, depth);
+        walk(&entry.children, depth + 1, f);
+    }
+}
+
+/// Renders every section table as a titled `Key | Value` table. Details of
+/// an entry (e.g. below `Result is`) follow it with an indented key, and
+/// failures are in bold.
+pub fn write_markdown<W: io::Write>(doc: &Document, writer: &mut W) -> io::Result<()> {
+    for (i, section) in doc.sections.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        match section {
+            Section::Table(t) => {
+                writeln!(writer, "## {}", t.title.trim())?;
+                writeln!(writer)?;
+                let mut table = Table::new(vec!["Key".to_string(), "Value".to_string()]);
+                walk(&t.entries, 0, &mut |entry, depth| {
+                    let (key, value) = cells(entry);
+                    let key = format!("{}{}", "&nbsp;&nbsp;&nbsp;&nbsp;".repeat(depth), key);
+                    let value = if is_failure(entry) {
+                        format!("**{}**", value)
+                    } else {
+                        value
+                    };
+                    table.rows.push(vec![key, value]);
+                });
+                table.write_markdown(writer)?;
+            }
+            Section::Note(n) => writeln!(writer, "{}", n.message.message.trim())?,
+        }
+    }
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders the same tables as `write_markdown` as an HTML fragment. Styles
+/// are inline so that the fragment keeps them when pasted into a wiki.
+pub fn write_html<W: io::Write>(doc: &Document, writer: &mut W) -> io::Result<()> {
+    for section in &doc.sections {
+        match section {
+            Section::Table(t) => {
+                writeln!(writer, "<h2>{}</h2>", escape_html(t.title.trim()))?;
+                writeln!(writer, "<table>")?;
+                writeln!(writer, "  <tr><th>Key</th><th>Value</th></tr>")?;
+                let mut rows = Vec::new();
+                walk(&t.entries, 0, &mut |entry, depth| {
+                    let (key, value) = cells(entry);
+                    let key_style = match depth {
+                        0 => String::new(),
+                        _ => format!(" style=\"padding-left: {}em\"", depth * 2),
+                    };
+                    let value_style = match is_failure(entry) {
+                        true => " style=\"color: #c62828; font-weight: bold\"",
+                        false => "",
+                    };
+                    rows.push(format!(
+                        "  <tr><td{}>{}</td><td{}>{}</td></tr>",
+                        key_style,
+                        escape_html(&key),
+                        value_style,
+                        escape_html(&value)
+                    ));
+                });
+                for row in rows {
+                    writeln!(writer, "{}", row)?;
+                }
+                writeln!(writer, "</table>")?;
+            }
+            Section::Note(n) => {
+                writeln!(writer, "<p>{}</p>", escape_html(n.message.message.trim()))?
+            }
+        }
+    }
+    Ok(())
+}
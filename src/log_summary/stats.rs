@@ -0,0 +1,176 @@
+use super::model::{flatten_dict, Dict};
+use super::parse_log_summary_file;
+use super::summary::as_f64;
+use crate::error::ParseError;
+use crate::format::{cell, format_float};
+use crate::submission::RunKey;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// Keys that must be the same in every run statistics are computed over,
+/// along with the model (`MODEL_KEY`).
+pub const IDENTITY_KEYS: &[&str] = &[
+    "mlperf_results_summary.sut_name",
+    "mlperf_results_summary.scenario",
+    "mlperf_results_summary.mode",
+];
+
+/// Name the model is reported under in `StatsError::Mismatch`.
+pub const MODEL_KEY: &str = "model";
+
+/// A run statistics are computed over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsRun {
+    /// The summary does not name the model, so it is taken from the
+    /// `<system>/<model>/<scenario>/<mode>` layout of the run directory.
+    pub model: Option<String>,
+    pub summary: Dict,
+}
+
+impl StatsRun {
+    pub fn from_file(file_path: &Path) -> Result<Self, ParseError> {
+        let summary = parse_log_summary_file(&file_path.to_string_lossy())?.to_dict();
+        let model = file_path
+            .parent()
+            .and_then(RunKey::from_dir_tail)
+            .map(|(key, _)| key.model);
+        Ok(StatsRun { model, summary })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsError {
+    NoRuns,
+    /// Runs with a different SUT, scenario, mode or model. `run` is the 0-based
+    /// index of the first run that differs from the first one.
+    Mismatch {
+        key: String,
+        expected: Option<String>,
+        found: Option<String>,
+        run: usize,
+    },
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |value: &Option<String>| value.clone().unwrap_or("(missing)".to_string());
+        match self {
+            StatsError::NoRuns => write!(f, "no runs to compute statistics over"),
+            StatsError::Mismatch {
+                key,
+                expected,
+                found,
+                run,
+            } => write!(
+                f,
+                "runs are not of the same configuration: '{}' is {} in run 1 but {} in run {}",
+                key,
+                side(expected),
+                side(found),
+                run + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+/// Statistics of a numeric key over the runs that have it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyStats {
+    /// Dotted key, e.g. `additional_stats.99_00_percentile_latency_ns`.
+    pub key: String,
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Sample standard deviation, 0 for a single run.
+    pub stddev: f64,
+    /// Coefficient of variation, `stddev / mean`. `None` when the mean is 0.
+    pub cv: Option<f64>,
+}
+
+impl KeyStats {
+    fn new(key: String, values: &[f64]) -> Self {
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let stddev = if count > 1 {
+            let variance =
+                values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+            variance.sqrt()
+        } else {
+            0.0
+        };
+        KeyStats {
+            key,
+            count,
+            mean,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            stddev,
+            cv: (mean != 0.0).then(|| stddev / mean.abs()),
+        }
+    }
+}
+
+impl fmt::Display for KeyStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: mean {}, min {}, max {}, stddev {}",
+            self.key,
+            format_float(self.mean),
+            format_float(self.min),
+            format_float(self.max),
+            format_float(self.stddev)
+        )?;
+        if let Some(cv) = self.cv {
+            write!(f, ", cv {:.2}%", cv * 100.0)?;
+        }
+        write!(f, " ({} runs)", self.count)
+    }
+}
+
+/// Checks that the runs share their `IDENTITY_KEYS` and model, and computes
+/// the statistics of every numeric key of their summaries, in the order the
+/// keys first appear. Keys missing from some runs are computed over the others.
+pub fn summary_stats(runs: &[StatsRun]) -> Result<Vec<KeyStats>, StatsError> {
+    let flat: Vec<_> = runs.iter().map(|run| flatten_dict(&run.summary)).collect();
+    if runs.is_empty() {
+        return Err(StatsError::NoRuns);
+    }
+
+    for key in IDENTITY_KEYS.iter().copied().chain([MODEL_KEY]) {
+        let value = |run: usize| match key {
+            MODEL_KEY => runs[run].model.clone(),
+            key => flat[run].get(key).map(cell),
+        };
+        let expected = value(0);
+        for run in 1..runs.len() {
+            let found = value(run);
+            if found != expected {
+                return Err(StatsError::Mismatch {
+                    key: key.to_string(),
+                    expected,
+                    found,
+                    run,
+                });
+            }
+        }
+    }
+
+    let mut values: IndexMap<&str, Vec<f64>> = IndexMap::new();
+    for run in &flat {
+        for (key, value) in run {
+            if let Some(value) = as_f64(value) {
+                values.entry(key.as_str()).or_default().push(value);
+            }
+        }
+    }
+    Ok(values
+        .into_iter()
+        .map(|(key, values)| KeyStats::new(key.to_string(), &values))
+        .collect())
+}
@@ -0,0 +1,411 @@
+use crate::loadgen::{Scenario, TestMode};
+use crate::log_summary::early_stopping::EarlyStoppingResult;
+use crate::log_summary::model::{Document, Section};
+use serde::Serialize;
+use serde_value::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+const RESULTS_SECTION: &str = "mlperf_results_summary";
+const STATS_SECTION: &str = "additional_stats";
+const PARAMETERS_SECTION: &str = "test_parameters_used";
+
+/// Error returned when a `Document` cannot be converted into an `MlperfSummary`.
+///
+/// `field` is the dotted path of the offending entry in `Document::to_dict`,
+/// e.g. `mlperf_results_summary.result_is.details.min_duration_satisfied`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SummaryError {
+    MissingField {
+        field: String,
+    },
+    InvalidType {
+        field: String,
+        expected: &'static str,
+        found: Value,
+    },
+}
+
+impl fmt::Display for SummaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SummaryError::MissingField { field } => write!(f, "missing field '{}'", field),
+            SummaryError::InvalidType {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "invalid type for field '{}': expected {}, found {:?}",
+                field, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SummaryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Validity {
+    Valid,
+    Invalid,
+}
+
+impl fmt::Display for Validity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Validity::Valid => write!(f, "VALID"),
+            Validity::Invalid => write!(f, "INVALID"),
+        }
+    }
+}
+
+/// Flags listed under the `Result is` line of the results section.
+///
+/// Only the duration and query count checks are printed for every scenario;
+/// the others depend on the scenario and loadgen version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConstraintsSatisfied {
+    pub min_duration: bool,
+    pub min_queries: bool,
+    pub early_stopping: Option<bool>,
+    pub performance_constraints: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyPercentile {
+    pub percentile: f64,
+    pub latency_ns: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub min_ns: u64,
+    pub max_ns: u64,
+    pub mean_ns: u64,
+    /// Latency percentiles sorted in ascending order (50.0, 90.0, ..., 99.9).
+    pub percentiles: Vec<LatencyPercentile>,
+}
+
+impl LatencyStats {
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        self.percentiles
+            .iter()
+            .find(|p| (p.percentile - percentile).abs() < 1e-9)
+            .map(|p| p.latency_ns)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MetricUnit {
+    SamplesPerSecond,
+    TokensPerSecond,
+    Nanoseconds,
+}
+
+impl fmt::Display for MetricUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetricUnit::SamplesPerSecond => write!(f, "samples/s"),
+            MetricUnit::TokensPerSecond => write!(f, "tokens/s"),
+            MetricUnit::Nanoseconds => write!(f, "ns"),
+        }
+    }
+}
+
+/// The metric MLPerf scores a run on, as printed in the results section.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrimaryMetric {
+    /// Normalized key of the metric (e.g. `completed_samples_per_second`).
+    pub name: String,
+    pub value: f64,
+    pub unit: MetricUnit,
+}
+
+/// Typed view of a parsed `log_summary.txt`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MlperfSummary {
+    pub sut_name: String,
+    pub scenario: Scenario,
+    pub mode: TestMode,
+    /// Numeric results reported next to the validity, keyed by their
+    /// normalized name (e.g. `samples_per_second`, `tokens_per_second_inferred`).
+    pub metrics: BTreeMap<String, f64>,
+    pub validity: Validity,
+    pub constraints: ConstraintsSatisfied,
+    /// Present when loadgen printed an `Early Stopping Result:` block.
+    pub early_stopping: Option<EarlyStoppingResult>,
+    /// `None` when the summary has no `Additional Stats` section.
+    pub latency: Option<LatencyStats>,
+    /// `None` when the summary has no `Test Parameters Used` section.
+    pub test_parameters: Option<BTreeMap<String, Value>>,
+}
+
+impl TryFrom<&Document> for MlperfSummary {
+    type Error = SummaryError;
+
+    fn try_from(doc: &Document) -> Result<Self, Self::Error> {
+        let root = serde_value::to_value(doc.to_dict()).unwrap();
+
+        let results = section(&root, RESULTS_SECTION)?;
+        let stats = optional_section(&root, STATS_SECTION)?;
+        let parameters = optional_section(&root, PARAMETERS_SECTION)?;
+
+        let results_field = Field::new(RESULTS_SECTION, results);
+        let result_is = results_field.get("result_is")?;
+        let details = result_is.get("details")?;
+
+        let early_stopping = doc.sections.iter().find_map(|section| match section {
+            Section::Table(table) => table
+                .entries
+                .iter()
+                .find_map(EarlyStoppingResult::from_entry),
+            Section::Note(_) => None,
+        });
+
+        let metrics = results
+            .iter()
+            .filter_map(|(key, value)| match (key, as_f64(value)) {
+                (Value::String(key), Some(value)) => Some((key.clone(), value)),
+                _ => None,
+            })
+            .collect();
+
+        let latency = stats.map(latency_stats).transpose()?;
+
+        Ok(MlperfSummary {
+            sut_name: results_field.get("sut_name")?.as_string()?,
+            scenario: results_field.get("scenario")?.as_scenario()?,
+            mode: results_field.get("mode")?.as_test_mode()?,
+            metrics,
+            validity: match result_is.get("value")?.as_string()?.as_str() {
+                "VALID" => Validity::Valid,
+                "INVALID" => Validity::Invalid,
+                _ => return Err(result_is.get("value")?.invalid("VALID or INVALID")),
+            },
+            constraints: ConstraintsSatisfied {
+                min_duration: details.get("min_duration_satisfied")?.as_bool()?,
+                min_queries: details.get("min_queries_satisfied")?.as_bool()?,
+                early_stopping: details
+                    .get_opt("early_stopping_satisfied")
+                    .map(|f| f.as_bool())
+                    .transpose()?,
+                performance_constraints: details
+                    .get_opt("performance_constraints_satisfied")
+                    .map(|f| f.as_bool())
+                    .transpose()?,
+            },
+            early_stopping,
+            latency,
+            test_parameters: parameters.map(|parameters| {
+                parameters
+                    .iter()
+                    .filter_map(|(key, value)| match key {
+                        Value::String(key) => Some((key.clone(), value.clone())),
+                        _ => None,
+                    })
+                    .collect()
+            }),
+        })
+    }
+}
+
+fn latency_stats(stats: &BTreeMap<Value, Value>) -> Result<LatencyStats, SummaryError> {
+    let stats_field = Field::new(STATS_SECTION, stats);
+    let mut percentiles: Vec<LatencyPercentile> = Vec::new();
+    for key in stats.keys() {
+        if let Value::String(key) = key {
+            if let Some(percentile) = parse_percentile_key(key) {
+                percentiles.push(LatencyPercentile {
+                    percentile,
+                    latency_ns: stats_field.get(key)?.as_u64()?,
+                });
+            }
+        }
+    }
+    percentiles.sort_by(|a, b| a.percentile.total_cmp(&b.percentile));
+
+    Ok(LatencyStats {
+        min_ns: stats_field.get("min_latency_ns")?.as_u64()?,
+        max_ns: stats_field.get("max_latency_ns")?.as_u64()?,
+        mean_ns: stats_field.get("mean_latency_ns")?.as_u64()?,
+        percentiles,
+    })
+}
+
+impl MlperfSummary {
+    /// Returns the metric loadgen scores for `self.scenario`.
+    ///
+    /// LLM benchmarks report tokens instead of samples for Offline and Server;
+    /// the token metric is preferred whenever loadgen printed it.
+    /// `Tokens per second (inferred)` is informational only and never picked.
+    pub fn primary_metric(&self) -> Result<PrimaryMetric, SummaryError> {
+        let candidates: &[(&str, MetricUnit)] = match self.scenario {
+            Scenario::Offline => &[
+                ("tokens_per_second", MetricUnit::TokensPerSecond),
+                ("samples_per_second", MetricUnit::SamplesPerSecond),
+            ],
+            Scenario::Server => &[
+                ("completed_tokens_per_second", MetricUnit::TokensPerSecond),
+                ("completed_samples_per_second", MetricUnit::SamplesPerSecond),
+            ],
+            Scenario::SingleStream => &[("90th_percentile_latency_ns", MetricUnit::Nanoseconds)],
+            Scenario::MultiStream => &[("99th_percentile_latency_ns", MetricUnit::Nanoseconds)],
+        };
+
+        candidates
+            .iter()
+            .find_map(|(name, unit)| {
+                self.metrics.get(*name).map(|value| PrimaryMetric {
+                    name: name.to_string(),
+                    value: *value,
+                    unit: *unit,
+                })
+            })
+            .ok_or_else(|| SummaryError::MissingField {
+                // report the samples based key, which every non-LLM benchmark prints
+                field: format!("{}.{}", RESULTS_SECTION, candidates[candidates.len() - 1].0),
+            })
+    }
+}
+
+/// `50_00_percentile_latency_ns` -> `50.0`
+fn parse_percentile_key(key: &str) -> Option<f64> {
+    let number = key.strip_suffix("_percentile_latency_ns")?;
+    number.replacen('_', ".", 1).parse::<f64>().ok()
+}
+
+pub(crate) fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::I8(v) => Some(*v as f64),
+        Value::I16(v) => Some(*v as f64),
+        Value::I32(v) => Some(*v as f64),
+        Value::I64(v) => Some(*v as f64),
+        Value::U8(v) => Some(*v as f64),
+        Value::U16(v) => Some(*v as f64),
+        Value::U32(v) => Some(*v as f64),
+        Value::U64(v) => Some(*v as f64),
+        Value::F32(v) => Some(*v as f64),
+        Value::F64(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn section<'a>(root: &'a Value, name: &str) -> Result<&'a BTreeMap<Value, Value>, SummaryError> {
+    optional_section(root, name)?.ok_or_else(|| SummaryError::MissingField {
+        field: name.to_string(),
+    })
+}
+
+fn optional_section<'a>(
+    root: &'a Value,
+    name: &str,
+) -> Result<Option<&'a BTreeMap<Value, Value>>, SummaryError> {
+    match root {
+        Value::Map(map) => match map.get(&Value::String(name.to_string())) {
+            Some(Value::Map(section)) => Ok(Some(section)),
+            Some(other) => Err(SummaryError::InvalidType {
+                field: name.to_string(),
+                expected: "section",
+                found: other.clone(),
+            }),
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// A value in the dict together with its dotted path, used for error reporting.
+struct Field<'a> {
+    path: String,
+    value: FieldValue<'a>,
+}
+
+enum FieldValue<'a> {
+    Map(&'a BTreeMap<Value, Value>),
+    Value(&'a Value),
+}
+
+impl<'a> Field<'a> {
+    fn new(path: &str, map: &'a BTreeMap<Value, Value>) -> Self {
+        Field {
+            path: path.to_string(),
+            value: FieldValue::Map(map),
+        }
+    }
+
+    fn get_opt(&self, key: &str) -> Option<Field<'a>> {
+        let map = match self.value {
+            FieldValue::Map(map) => map,
+            FieldValue::Value(Value::Map(map)) => map,
+            FieldValue::Value(_) => return None,
+        };
+        map.get(&Value::String(key.to_string())).map(|value| Field {
+            path: format!("{}.{}", self.path, key),
+            value: FieldValue::Value(value),
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Field<'a>, SummaryError> {
+        self.get_opt(key).ok_or_else(|| SummaryError::MissingField {
+            field: format!("{}.{}", self.path, key),
+        })
+    }
+
+    fn value(&self) -> Value {
+        match self.value {
+            FieldValue::Map(map) => Value::Map(map.clone()),
+            FieldValue::Value(value) => value.clone(),
+        }
+    }
+
+    fn invalid(&self, expected: &'static str) -> SummaryError {
+        SummaryError::InvalidType {
+            field: self.path.clone(),
+            expected,
+            found: self.value(),
+        }
+    }
+
+    fn as_string(&self) -> Result<String, SummaryError> {
+        match self.value {
+            FieldValue::Value(Value::String(s)) => Ok(s.clone()),
+            _ => Err(self.invalid("string")),
+        }
+    }
+
+    fn as_scenario(&self) -> Result<Scenario, SummaryError> {
+        self.as_string()?
+            .parse()
+            .map_err(|_| self.invalid("Offline, Server, SingleStream or MultiStream"))
+    }
+
+    /// Also accepts the names without their suffix, e.g. `Performance`.
+    fn as_test_mode(&self) -> Result<TestMode, SummaryError> {
+        let mode = self.as_string()?;
+        let mode = match mode.as_str() {
+            "Submission" => "SubmissionRun",
+            "Accuracy" => "AccuracyOnly",
+            "Performance" => "PerformanceOnly",
+            other => other,
+        };
+        mode.parse().map_err(|_| {
+            self.invalid("SubmissionRun, AccuracyOnly, PerformanceOnly or FindPeakPerformance")
+        })
+    }
+
+    fn as_bool(&self) -> Result<bool, SummaryError> {
+        match self.value {
+            FieldValue::Value(Value::Bool(b)) => Ok(*b),
+            _ => Err(self.invalid("Yes or No")),
+        }
+    }
+
+    fn as_u64(&self) -> Result<u64, SummaryError> {
+        match self.value {
+            FieldValue::Value(Value::U64(v)) => Ok(*v),
+            FieldValue::Value(Value::I64(v)) if *v >= 0 => Ok(*v as u64),
+            _ => Err(self.invalid("unsigned integer")),
+        }
+    }
+}
@@ -1,8 +1,26 @@
-use env_logger;
-use mlperf_log_parser::{log_detail::save_log_detail, log_summary::save_summary};
+use mlperf_log_parser::accuracy_log::{save_accuracy_log, DataType};
+use mlperf_log_parser::log_detail::issues::{issues, Severity};
+use mlperf_log_parser::log_detail::{
+    parse_mlperf_log_detail_file, save_log_detail, save_log_detail_as_csv, settings_diff,
+    TestSettings,
+};
+use mlperf_log_parser::log_summary::diff::{
+    diff_summaries, missing_watched_keys, parse_threshold, regressions, unknown_thresholds,
+    Direction, Threshold,
+};
+use mlperf_log_parser::log_summary::model::{
+    DictOptions, DuplicateKey, DuplicateKeyPolicy, UnitMode,
+};
+use mlperf_log_parser::log_summary::stats::{summary_stats, StatsRun};
+use mlperf_log_parser::log_summary::{parse_log_summary_file, save_summary_with};
+use mlperf_log_parser::submission::{find_log_files, SUMMARY_FILE};
+use mlperf_log_parser::table::{summary_row, Table, DEFAULT_COLUMNS};
+use mlperf_log_parser::trace_log::{compare_latency_stats, trace_latencies_file, SAMPLE_EVENT};
+use mlperf_log_parser::{cross_check, scan_submission, Conf, MlperfSummary, ParseError, RunLogs};
+use std::collections::HashSet;
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use structopt::StructOpt;
 
@@ -56,6 +74,157 @@ fn validate_detail_input_file(path: String) -> Result<(), String> {
     Ok(())
 }
 
+fn parse_max_increase(s: &str) -> Result<Threshold, String> {
+    parse_threshold(s, Direction::Increase)
+}
+
+fn parse_max_decrease(s: &str) -> Result<Threshold, String> {
+    parse_threshold(s, Direction::Decrease)
+}
+
+/// Prints parse errors, also when wrapped in an `io::Error`, as annotated diagnostics
+/// and everything else as a plain message.
+fn report_error(e: &(dyn std::error::Error + 'static)) {
+    let parse_error = e.downcast_ref::<ParseError>().or_else(|| {
+        e.downcast_ref::<io::Error>()
+            .and_then(|e| e.get_ref())
+            .and_then(|inner| inner.downcast_ref::<ParseError>())
+    });
+    match parse_error {
+        Some(parse_error) => eprintln!("{}", parse_error),
+        None => eprintln!("Error: {}", e),
+    }
+}
+
+fn save_detail<W: io::Write>(
+    input_file: &str,
+    writer: &mut W,
+    format: &str,
+    metadata_columns: &[String],
+) -> io::Result<()> {
+    if format == "csv" && !metadata_columns.is_empty() {
+        let columns: Vec<&str> = metadata_columns.iter().map(|c| c.as_str()).collect();
+        save_log_detail_as_csv(input_file, writer, &columns)
+    } else {
+        save_log_detail(input_file, writer, format)
+    }
+}
+
+/// Writes the `user.conf` lines reproducing the effective settings of a detail log.
+fn save_conf(input_file: &str, conf_file: &Path, model: &str) -> io::Result<()> {
+    let entries = parse_mlperf_log_detail_file(input_file)?;
+    let settings = TestSettings::try_from(entries.as_slice())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if conf_file.to_str() == Some("-") {
+        settings.write_conf(model, &mut io::stdout())
+    } else {
+        let mut file = fs::File::create(conf_file)?;
+        settings.write_conf(model, &mut file)?;
+        eprintln!(
+            "Saved effective settings of {} to {}",
+            input_file,
+            conf_file.display()
+        );
+        Ok(())
+    }
+}
+
+/// One line per run: where it is, its result and how many issues loadgen logged.
+fn print_run(run: &RunLogs) {
+    let mut line = run.key.to_string();
+    if let Some(name) = &run.run {
+        line.push_str(&format!("/{}", name));
+    }
+    if let Some(summary) = &run.summary {
+        line.push_str(&format!(" {}", summary.validity));
+        if let Ok(metric) = summary.primary_metric() {
+            line.push_str(&format!(
+                " {}={} {}",
+                metric.name, metric.value, metric.unit
+            ));
+        }
+    }
+    if let Some(entries) = run.accuracy_entries {
+        line.push_str(&format!(" accuracy_entries={}", entries));
+    }
+    if !run.issues.is_empty() {
+        line.push_str(&format!(" ({} issue(s))", run.issues.len()));
+    }
+    println!("{}", line);
+}
+
+/// Columns of `Cli::Table` that are neither default ones nor keys of any summary.
+#[derive(Debug)]
+struct UnknownColumns(Vec<String>);
+
+impl std::fmt::Display for UnknownColumns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let columns: Vec<String> = self.0.iter().map(|c| format!("'{}'", c)).collect();
+        write!(
+            f,
+            "unknown column(s) {}, neither a default column nor a key of any summary",
+            columns.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownColumns {}
+
+/// Builds the table of `Cli::Table`, reporting summaries that fail to parse;
+/// the flag is set when any did.
+fn summary_table(inputs: &[PathBuf], columns: &[String]) -> Result<(Table, bool), UnknownColumns> {
+    let columns = if columns.is_empty() {
+        DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect()
+    } else {
+        columns.to_vec()
+    };
+    let mut table = Table::new(columns);
+    let mut keys = HashSet::new();
+    let mut failed = false;
+    for input in inputs {
+        let files = if input.is_dir() {
+            let (files, failures) = find_log_files(input, SUMMARY_FILE);
+            for failure in &failures {
+                eprintln!("Error: {}", failure);
+            }
+            failed |= !failures.is_empty();
+            files
+        } else {
+            vec![input.clone()]
+        };
+        for file in files {
+            match parse_log_summary_file(&file.to_string_lossy()) {
+                Ok(doc) => {
+                    let row = summary_row(&file, &doc);
+                    keys.extend(row.keys().cloned());
+                    table.push(&row);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    let unknown: Vec<String> = table
+        .columns
+        .iter()
+        .filter(|c| !DEFAULT_COLUMNS.contains(&c.as_str()) && !keys.contains(*c))
+        .cloned()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(UnknownColumns(unknown));
+    }
+    Ok((table, failed))
+}
+
+fn report_warnings(warnings: &[DuplicateKey]) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
 #[derive(StructOpt)]
 #[structopt(name = "mlperf-log-parser", author, about)]
 pub enum Cli {
@@ -69,24 +238,204 @@ pub enum Cli {
         #[structopt(parse(from_os_str))]
         output_file: PathBuf,
 
-        /// Output format (.json | .yaml)
+        /// Output format (.json | .yaml | .csv | .md | .html)
         #[structopt(short, long, default_value = "json")]
         format: String,
+
+        /// How to handle a key repeated within a section (error | first | last | list)
+        #[structopt(long, default_value = "last")]
+        duplicate_keys: DuplicateKeyPolicy,
+
+        /// How to output values with a unit (strip | annotate | ns | us | ms | s)
+        #[structopt(long, default_value = "strip")]
+        units: UnitMode,
     },
 
     /// Parse detailed logs
+    ///
+    /// Stops at the first :::MLLOG line with malformed JSON and reports its line number,
+    /// where earlier versions skipped the line. Lines without the ":::MLLOG " prefix are
+    /// skipped with a warning.
     LogDetail {
         /// Input file path
         #[structopt(parse(from_os_str), validator = validate_detail_input_file)]
         input_file: std::path::PathBuf,
 
-        /// Output file path
+        /// Output file path (optional with --check or --emit-conf)
         #[structopt(parse(from_os_str))]
-        output_file: std::path::PathBuf,
+        output_file: Option<std::path::PathBuf>,
 
-        /// Output format (.json | .yaml)
+        /// Output format (.json | .yaml | .csv)
         #[structopt(short, long, default_value = "json")]
         format: String,
+
+        /// Comma separated metadata fields written as columns in the csv format
+        /// (default: is_error,is_warning,file,line_no)
+        #[structopt(long, use_delimiter = true)]
+        metadata_columns: Vec<String>,
+
+        /// List loadgen errors and warnings, exiting with 1 if any error was logged
+        #[structopt(long)]
+        check: bool,
+
+        /// Write a user.conf reproducing the run's effective settings (- for stdout)
+        #[structopt(long, parse(from_os_str))]
+        emit_conf: Option<PathBuf>,
+
+        /// Model name used in the lines written by --emit-conf
+        #[structopt(long, default_value = "*")]
+        model: String,
+    },
+
+    /// Report settings whose effective value differs from the requested one (Requires *log_detail.txt file)
+    SettingsDiff {
+        /// Input file path
+        #[structopt(parse(from_os_str), validator = validate_detail_input_file)]
+        input_file: PathBuf,
+
+        /// Output format (text | json)
+        #[structopt(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Check that the settings in loadgen config files were applied to a run (Requires *log_detail.txt file)
+    CheckConf {
+        /// Input file path
+        #[structopt(parse(from_os_str), validator = validate_detail_input_file)]
+        input_file: PathBuf,
+
+        /// Config files in the order loadgen loaded them (ex, mlperf.conf user.conf)
+        #[structopt(long = "conf", required = true, number_of_values = 1)]
+        conf_files: Vec<String>,
+
+        /// Model name the config files were loaded for
+        #[structopt(long)]
+        model: String,
+    },
+
+    /// Report fields the summary and detail logs of a run disagree on
+    CrossCheck {
+        /// Summary file path (ex, mlperf_log_summary.txt)
+        #[structopt(parse(from_os_str), validator = validate_summary_input_file)]
+        summary_file: PathBuf,
+
+        /// Detail file path (ex, mlperf_log_detail.txt)
+        #[structopt(parse(from_os_str), validator = validate_detail_input_file)]
+        detail_file: PathBuf,
+    },
+
+    /// Parse every run of a submission laid out as results/<system>/<model>/<scenario>/<mode>
+    Scan {
+        /// Submission root or results directory
+        #[structopt(parse(from_os_str))]
+        submission_dir: PathBuf,
+
+        /// Output format (text | json)
+        #[structopt(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Write one row per summary log as a CSV or Markdown table
+    Table {
+        /// Summary files, or directories searched for mlperf_log_summary.txt
+        #[structopt(parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// Output format (csv | markdown)
+        #[structopt(short, long, default_value = "csv")]
+        format: String,
+
+        /// Comma separated columns; besides the default ones any flattened summary key
+        /// (ex, test_parameters_used.target_qps) can be used
+        #[structopt(short, long, use_delimiter = true)]
+        columns: Vec<String>,
+
+        /// Output file path (- for stdout)
+        #[structopt(short, long, parse(from_os_str), default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Compare two summary logs, exiting with 1 if a change exceeds a threshold
+    Diff {
+        /// Baseline summary file path
+        #[structopt(parse(from_os_str), validator = validate_summary_input_file)]
+        old_file: PathBuf,
+
+        /// Summary file path compared against the baseline
+        #[structopt(parse(from_os_str), validator = validate_summary_input_file)]
+        new_file: PathBuf,
+
+        /// Largest allowed increase in percent as KEY=PERCENT, where KEY is a flattened
+        /// summary key or its trailing part (ex, 99_00_percentile_latency_ns=5)
+        #[structopt(long, number_of_values = 1, parse(try_from_str = parse_max_increase))]
+        max_increase: Vec<Threshold>,
+
+        /// Largest allowed decrease in percent as KEY=PERCENT (ex, samples_per_second=2)
+        #[structopt(long, number_of_values = 1, parse(try_from_str = parse_max_decrease))]
+        max_decrease: Vec<Threshold>,
+
+        /// Exit with 1 when a key watched by a threshold is missing from either summary,
+        /// which is otherwise only a warning
+        #[structopt(long)]
+        fail_on_missing: bool,
+
+        /// Output format (text | json)
+        #[structopt(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Mean, min, max, stddev and coefficient of variation of every numeric key over repeated runs
+    Stats {
+        /// Summary files of runs of the same SUT, scenario and model; the model is read from the
+        /// <system>/<model>/<scenario>/<mode> layout of their paths
+        #[structopt(parse(from_os_str), required = true, validator = validate_summary_input_file)]
+        input_files: Vec<PathBuf>,
+
+        /// Output format (text | json)
+        #[structopt(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Convert mlperf_log_accuracy.json to JSON Lines or CSV, decoding the hex payloads
+    AccuracyLog {
+        /// Input file path (ex, mlperf_log_accuracy.json)
+        #[structopt(parse(from_os_str))]
+        input_file: PathBuf,
+
+        /// Output file path (- for stdout)
+        #[structopt(parse(from_os_str))]
+        output_file: PathBuf,
+
+        /// Output format (jsonl | csv)
+        #[structopt(short, long, default_value = "jsonl")]
+        format: String,
+
+        /// Type the payloads are decoded as, little-endian (hex | u8 | i32 | i64 | f32)
+        #[structopt(short, long, default_value = "hex")]
+        data_type: DataType,
+    },
+
+    /// Compute per-query latencies from mlperf_log_trace.json, optionally checking them against a summary
+    TraceLog {
+        /// Input file path (ex, mlperf_log_trace.json)
+        #[structopt(parse(from_os_str))]
+        input_file: PathBuf,
+
+        /// Name of the events spanning a query from issue to completion
+        #[structopt(long, default_value = SAMPLE_EVENT)]
+        name: String,
+
+        /// Summary file whose latencies are compared, exiting with 1 if they differ
+        #[structopt(long, parse(from_os_str), validator = validate_summary_input_file)]
+        summary: Option<PathBuf>,
+
+        /// Relative difference in percent tolerated when comparing with the summary
+        #[structopt(long, default_value = "1")]
+        tolerance: f64,
+
+        /// Output format (text | json)
+        #[structopt(short, long, default_value = "text")]
+        format: String,
     },
 }
 
@@ -95,6 +444,15 @@ impl std::fmt::Display for Cli {
         match self {
             Cli::LogSummary { .. } => write!(f, "log_summary"),
             Cli::LogDetail { .. } => write!(f, "log_detail"),
+            Cli::SettingsDiff { .. } => write!(f, "settings_diff"),
+            Cli::CheckConf { .. } => write!(f, "check_conf"),
+            Cli::CrossCheck { .. } => write!(f, "cross_check"),
+            Cli::Scan { .. } => write!(f, "scan"),
+            Cli::Table { .. } => write!(f, "table"),
+            Cli::Diff { .. } => write!(f, "diff"),
+            Cli::Stats { .. } => write!(f, "stats"),
+            Cli::AccuracyLog { .. } => write!(f, "accuracy_log"),
+            Cli::TraceLog { .. } => write!(f, "trace_log"),
         }
     }
 }
@@ -103,76 +461,381 @@ fn main() {
     env_logger::init();
     let cli = Cli::from_args();
 
-    match &cli {
+    match run(&cli) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            report_error(e.as_ref());
+            process::exit(1);
+        }
+    }
+}
+
+/// Runs a subcommand. `Ok(false)` means it ran but found problems, which it has
+/// already reported; errors that kept it from running are left to `main`.
+fn run(cli: &Cli) -> Result<bool, Box<dyn std::error::Error>> {
+    match cli {
         Cli::LogSummary {
             input_file,
             output_file,
             format,
+            duplicate_keys,
+            units,
         } => {
+            let options = DictOptions {
+                duplicate_keys: *duplicate_keys,
+                units: *units,
+            };
             let output_path = output_file.to_str().unwrap();
             if output_path == "-" {
                 // 표준출력으로 결과 전송
                 let stdout = io::stdout();
                 let mut handle = io::BufWriter::new(stdout);
-                if let Err(e) = save_summary(input_file.to_str().unwrap(), &mut handle, format) {
-                    eprintln!("Error: {}", e);
-                    process::exit(1);
-                }
+                let warnings =
+                    save_summary_with(input_file.to_str().unwrap(), &mut handle, format, &options)?;
+                report_warnings(&warnings);
             } else {
                 // 파일로 저장
-                let mut file = match fs::File::create(output_path) {
-                    Ok(file) => file,
-                    Err(e) => {
-                        eprintln!("Error: {}", e);
-                        process::exit(1);
-                    }
-                };
-                if let Err(e) = save_summary(input_file.to_str().unwrap(), &mut file, format) {
-                    eprintln!("Error: {}", e);
-                    process::exit(1);
-                }
+                let mut file = fs::File::create(output_path)?;
+                let warnings =
+                    save_summary_with(input_file.to_str().unwrap(), &mut file, format, &options)?;
+                report_warnings(&warnings);
                 eprintln!(
                     "Command {} parsed {} file and saved to {}",
-                    cli.to_string(),
+                    cli,
                     input_file.display(),
                     output_file.display()
                 );
             }
+            Ok(true)
         }
         Cli::LogDetail {
             input_file,
             output_file,
             format,
+            metadata_columns,
+            check,
+            emit_conf,
+            model,
         } => {
-            let output_path = output_file.to_str().unwrap();
-            if output_path == "-" {
-                // 표준출력으로 결과 전송
-                let stdout = io::stdout();
-                let mut handle = io::BufWriter::new(stdout);
-                if let Err(e) = save_log_detail(input_file.to_str().unwrap(), &mut handle, format) {
-                    eprintln!("Error: {}", e);
-                    process::exit(1);
+            if output_file.is_none() && !check && emit_conf.is_none() {
+                return Err(
+                    "Output file path is required unless --check or --emit-conf is given".into(),
+                );
+            }
+            if let Some(output_file) = output_file {
+                let output_path = output_file.to_str().unwrap();
+                if output_path == "-" {
+                    // 표준출력으로 결과 전송
+                    let stdout = io::stdout();
+                    let mut handle = io::BufWriter::new(stdout);
+                    save_detail(
+                        input_file.to_str().unwrap(),
+                        &mut handle,
+                        format,
+                        metadata_columns,
+                    )?;
+                } else {
+                    // 파일로 저장
+                    let mut file = fs::File::create(output_path)?;
+                    save_detail(
+                        input_file.to_str().unwrap(),
+                        &mut file,
+                        format,
+                        metadata_columns,
+                    )?;
+                    eprintln!(
+                        "Command {} parsed {} file and saved to {}",
+                        cli,
+                        input_file.display(),
+                        output_file.display()
+                    );
+                }
+            }
+            if let Some(conf_file) = emit_conf {
+                save_conf(input_file.to_str().unwrap(), conf_file, model)?;
+            }
+            if *check {
+                let entries = parse_mlperf_log_detail_file(input_file.to_str().unwrap())?;
+                let issues = issues(&entries);
+                for issue in &issues {
+                    eprintln!("{}", issue);
+                }
+                let errors = issues
+                    .iter()
+                    .filter(|i| i.severity == Severity::Error)
+                    .count();
+                eprintln!(
+                    "{} error(s), {} warning(s) in {}",
+                    errors,
+                    issues.len() - errors,
+                    input_file.display()
+                );
+                return Ok(errors == 0);
+            }
+            Ok(true)
+        }
+        Cli::SettingsDiff { input_file, format } => {
+            let entries = parse_mlperf_log_detail_file(input_file.to_str().unwrap())?;
+            let diffs = settings_diff(&entries);
+            match format.as_str() {
+                "text" => {
+                    if diffs.is_empty() {
+                        println!("All effective settings match the requested ones.");
+                    }
+                    for diff in &diffs {
+                        println!("{}", diff);
+                    }
+                }
+                "json" => println!("{}", serde_json::to_string_pretty(&diffs).unwrap()),
+                _ => return Err("Invalid format. Use 'text' or 'json'.".into()),
+            }
+            Ok(true)
+        }
+        Cli::CheckConf {
+            input_file,
+            conf_files,
+            model,
+        } => {
+            let mut conf = Conf::default();
+            for conf_file in conf_files {
+                conf.extend(Conf::from_file(conf_file)?);
+            }
+            let entries = parse_mlperf_log_detail_file(input_file.to_str().unwrap())?;
+            let settings = TestSettings::try_from(entries.as_slice())?;
+            let mismatches = conf.check(model, &settings);
+            for mismatch in &mismatches {
+                println!("{}", mismatch);
+            }
+            if !mismatches.is_empty() {
+                return Ok(false);
+            }
+            eprintln!(
+                "All settings for {}.{} were applied",
+                model, settings.scenario
+            );
+            Ok(true)
+        }
+        Cli::CrossCheck {
+            summary_file,
+            detail_file,
+        } => {
+            let mismatches = cross_check(
+                summary_file.to_str().unwrap(),
+                detail_file.to_str().unwrap(),
+            )?;
+            for mismatch in &mismatches {
+                println!("{}", mismatch);
+            }
+            if !mismatches.is_empty() {
+                return Ok(false);
+            }
+            eprintln!(
+                "{} and {} agree",
+                summary_file.display(),
+                detail_file.display()
+            );
+            Ok(true)
+        }
+        Cli::Scan {
+            submission_dir,
+            format,
+        } => {
+            let scan = scan_submission(submission_dir);
+            match format.as_str() {
+                "text" => scan.iter().for_each(print_run),
+                "json" => {
+                    let output = serde_json::json!({
+                        "runs": scan.iter().collect::<Vec<_>>(),
+                        "failures": scan.failures,
+                        "warnings": scan.warnings,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&output).unwrap());
                 }
+                _ => return Err("Invalid format. Use 'text' or 'json'.".into()),
+            }
+            for warning in &scan.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            for failure in &scan.failures {
+                eprintln!("Error: {}", failure);
+            }
+            Ok(scan.failures.is_empty())
+        }
+        Cli::Table {
+            inputs,
+            format,
+            columns,
+            output,
+        } => {
+            let (table, failed) = summary_table(inputs, columns)?;
+            if output.to_str() == Some("-") {
+                table.write(&mut io::stdout(), format)?;
             } else {
-                // 파일로 저장
-                let mut file = match fs::File::create(output_path) {
-                    Ok(file) => file,
-                    Err(e) => {
-                        eprintln!("Error: {}", e);
-                        process::exit(1);
+                let mut file = fs::File::create(output)?;
+                table.write(&mut file, format)?;
+            }
+            Ok(!failed)
+        }
+        Cli::Diff {
+            old_file,
+            new_file,
+            max_increase,
+            max_decrease,
+            fail_on_missing,
+            format,
+        } => {
+            let mut summaries = Vec::new();
+            for input_file in [old_file, new_file] {
+                summaries.push(parse_log_summary_file(input_file.to_str().unwrap())?.to_dict());
+            }
+            let (old, new) = (&summaries[0], &summaries[1]);
+            let thresholds: Vec<Threshold> =
+                max_increase.iter().chain(max_decrease).cloned().collect();
+            let unknown = unknown_thresholds(old, new, &thresholds);
+            if !unknown.is_empty() {
+                for threshold in unknown {
+                    eprintln!(
+                        "Error: threshold key '{}' matches no key of either summary",
+                        threshold.key
+                    );
+                }
+                return Ok(false);
+            }
+            let changes = diff_summaries(old, new);
+            let regressions = regressions(&changes, &thresholds);
+            let missing = missing_watched_keys(&changes, &thresholds);
+            match format.as_str() {
+                "text" => {
+                    if changes.is_empty() {
+                        println!("No changes.");
+                    }
+                    for change in &changes {
+                        println!("{}", change);
                     }
-                };
-                if let Err(e) = save_log_detail(input_file.to_str().unwrap(), &mut file, format) {
+                }
+                "json" => {
+                    let report = serde_json::json!({
+                        "changes": changes,
+                        "regressions": regressions,
+                        "missing": missing,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+                }
+                _ => return Err("Invalid format. Use 'text' or 'json'.".into()),
+            }
+            for regression in &regressions {
+                eprintln!("Regression: {}", regression);
+            }
+            let level = if *fail_on_missing { "Error" } else { "Warning" };
+            for change in &missing {
+                eprintln!("{}: watched key is missing, {}", level, change);
+            }
+            Ok(regressions.is_empty() && (missing.is_empty() || !fail_on_missing))
+        }
+        Cli::Stats {
+            input_files,
+            format,
+        } => {
+            let mut runs = Vec::new();
+            for input_file in input_files {
+                runs.push(StatsRun::from_file(input_file)?);
+            }
+            let stats = match summary_stats(&runs) {
+                Ok(stats) => stats,
+                Err(e) => {
                     eprintln!("Error: {}", e);
-                    process::exit(1);
+                    for (i, input_file) in input_files.iter().enumerate() {
+                        eprintln!("  run {}: {}", i + 1, input_file.display());
+                    }
+                    return Ok(false);
                 }
-                eprintln!(
-                    "Command {} parsed {} file and saved to {}",
-                    cli.to_string(),
-                    input_file.display(),
-                    output_file.display()
+            };
+            match format.as_str() {
+                "text" => {
+                    for key_stats in &stats {
+                        println!("{}", key_stats);
+                    }
+                }
+                "json" => println!("{}", serde_json::to_string_pretty(&stats).unwrap()),
+                _ => return Err("Invalid format. Use 'text' or 'json'.".into()),
+            }
+            Ok(true)
+        }
+        Cli::AccuracyLog {
+            input_file,
+            output_file,
+            format,
+            data_type,
+        } => {
+            let input_file = input_file.to_str().unwrap();
+            if output_file.to_str() == Some("-") {
+                let mut handle = io::BufWriter::new(io::stdout());
+                save_accuracy_log(input_file, &mut handle, format, *data_type)?;
+            } else {
+                let mut writer = io::BufWriter::new(fs::File::create(output_file)?);
+                save_accuracy_log(input_file, &mut writer, format, *data_type)?;
+            }
+            Ok(true)
+        }
+        Cli::TraceLog {
+            input_file,
+            name,
+            summary,
+            tolerance,
+            format,
+        } => {
+            let latencies = trace_latencies_file(input_file.to_str().unwrap(), name)?;
+            let Some(stats) = latencies.stats() else {
+                return Err(
+                    format!("no '{}' events found in {}", name, input_file.display()).into(),
                 );
+            };
+            let mismatches = match summary {
+                Some(summary_file) => {
+                    let doc = parse_log_summary_file(summary_file.to_str().unwrap())?;
+                    let Some(latency) = MlperfSummary::try_from(&doc)?.latency else {
+                        return Err(format!(
+                            "{} has no Additional Stats section to compare with",
+                            summary_file.display()
+                        )
+                        .into());
+                    };
+                    compare_latency_stats(&latency, &stats, tolerance / 100.0)
+                }
+                None => Vec::new(),
+            };
+            match format.as_str() {
+                "text" => {
+                    println!("queries: {}", latencies.latencies.len());
+                    if latencies.unmatched > 0 {
+                        println!("unmatched events: {}", latencies.unmatched);
+                    }
+                    println!("min latency (ns): {}", stats.min_ns);
+                    println!("max latency (ns): {}", stats.max_ns);
+                    println!("mean latency (ns): {}", stats.mean_ns);
+                    for p in &stats.percentiles {
+                        println!(
+                            "{:.2} percentile latency (ns): {}",
+                            p.percentile, p.latency_ns
+                        );
+                    }
+                }
+                "json" => {
+                    let report = serde_json::json!({
+                        "queries": latencies.latencies.len(),
+                        "unmatched": latencies.unmatched,
+                        "latency": stats,
+                        "mismatches": mismatches,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+                }
+                _ => return Err("Invalid format. Use 'text' or 'json'.".into()),
+            }
+            for mismatch in &mismatches {
+                eprintln!("Mismatch: {}", mismatch);
             }
+            Ok(mismatches.is_empty())
         }
     }
 }
@@ -0,0 +1,268 @@
+use crate::accuracy_log::AccuracyLogReader;
+use crate::log_detail::{issues, parse_mlperf_log_detail_file, Issue, TestSettings};
+use crate::log_summary::parse_log_summary_file;
+use crate::log_summary::summary::MlperfSummary;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SUMMARY_FILE: &str = "mlperf_log_summary.txt";
+pub const DETAIL_FILE: &str = "mlperf_log_detail.txt";
+pub const ACCURACY_FILE: &str = "mlperf_log_accuracy.json";
+
+/// Identifies a run in the MLCommons layout
+/// `results/<system>/<model>/<scenario>/<mode>[/run_N]`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct RunKey {
+    pub system: String,
+    pub model: String,
+    pub scenario: String,
+    /// `performance` or `accuracy`.
+    pub mode: String,
+}
+
+impl RunKey {
+    /// Reads the key from a run directory relative to the `results`
+    /// directory, `<system>/<model>/<scenario>/<mode>[/run_N]`, also
+    /// returning `run_N`. Directories at any other depth are not runs.
+    pub fn from_dir(relative: &Path) -> Option<(RunKey, Option<String>)> {
+        let (parts, run) = split_run(relative);
+        Some((Self::from_parts(parts)?, run))
+    }
+
+    /// Like `from_dir`, but reads the key from the last components of `dir`
+    /// for paths whose `results` directory is not known.
+    pub fn from_dir_tail(dir: &Path) -> Option<(RunKey, Option<String>)> {
+        let (mut parts, run) = split_run(dir);
+        let tail = parts.split_off(parts.len().checked_sub(4)?);
+        Some((Self::from_parts(tail)?, run))
+    }
+
+    fn from_parts(parts: Vec<String>) -> Option<RunKey> {
+        let [system, model, scenario, mode] = <[String; 4]>::try_from(parts).ok()?;
+        if mode != "performance" && mode != "accuracy" {
+            return None;
+        }
+        Some(RunKey {
+            system,
+            model,
+            scenario,
+            mode,
+        })
+    }
+}
+
+/// Components of a directory, without its trailing `run_N`.
+fn split_run(dir: &Path) -> (Vec<String>, Option<String>) {
+    let mut parts: Vec<String> = dir
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let run = match parts.last() {
+        Some(last) if last.starts_with("run_") => parts.pop(),
+        _ => None,
+    };
+    (parts, run)
+}
+
+impl fmt::Display for RunKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}",
+            self.system, self.model, self.scenario, self.mode
+        )
+    }
+}
+
+/// Logs found in a single run directory. Logs that failed to parse are left
+/// out and reported in `Scan::failures`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunLogs {
+    #[serde(flatten)]
+    pub key: RunKey,
+    /// `run_1`, ... for performance runs kept in their own directory.
+    pub run: Option<String>,
+    pub dir: PathBuf,
+    pub summary: Option<MlperfSummary>,
+    pub settings: Option<TestSettings>,
+    /// Errors and warnings loadgen logged in the detail log.
+    pub issues: Vec<Issue>,
+    /// Number of entries in `mlperf_log_accuracy.json`.
+    pub accuracy_entries: Option<usize>,
+}
+
+/// A path of the submission tree that could not be read or parsed, or that
+/// holds logs outside the run layout.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanFailure {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+impl fmt::Display for ScanFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scan {
+    /// Runs keyed by (system, model, scenario, mode), each holding its
+    /// `run_N` directories in order.
+    pub runs: BTreeMap<RunKey, Vec<RunLogs>>,
+    pub failures: Vec<ScanFailure>,
+    /// Directories holding logs outside the `<system>/<model>/<scenario>/<mode>`
+    /// layout, e.g. copies kept next to the runs. They are skipped.
+    pub warnings: Vec<ScanFailure>,
+}
+
+impl Scan {
+    pub fn iter(&self) -> impl Iterator<Item = &RunLogs> {
+        self.runs.values().flatten()
+    }
+}
+
+/// Walks a submission and parses every summary, detail and accuracy log
+/// found under `results/`. `root` may be the submission root, its `results`
+/// directory, or any directory laid out like it.
+///
+/// Unreadable directories and logs that fail to parse are collected in
+/// `Scan::failures` instead of aborting the scan, and directories outside the
+/// layout in `Scan::warnings`.
+pub fn scan_submission(root: &Path) -> Scan {
+    let results = root.join("results");
+    let results = if results.is_dir() {
+        results
+    } else {
+        root.to_path_buf()
+    };
+
+    let mut scan = Scan::default();
+    let mut dirs = Vec::new();
+    collect_run_dirs(&results, &mut dirs, &mut scan.failures);
+    dirs.sort();
+
+    for dir in dirs {
+        let relative = dir.strip_prefix(&results).unwrap_or(&dir);
+        let Some((key, run)) = RunKey::from_dir(relative) else {
+            scan.warnings.push(ScanFailure {
+                path: dir.clone(),
+                error: "not in <system>/<model>/<scenario>/<mode> layout".to_string(),
+            });
+            continue;
+        };
+        let logs = parse_run(key.clone(), run, &dir, &mut scan.failures);
+        scan.runs.entry(key).or_default().push(logs);
+    }
+    scan
+}
+
+/// Every file named `file_name` under `root`, in path order, along with the
+/// directories that could not be read.
+pub fn find_log_files(root: &Path, file_name: &str) -> (Vec<PathBuf>, Vec<ScanFailure>) {
+    let mut dirs = Vec::new();
+    let mut failures = Vec::new();
+    collect_run_dirs(root, &mut dirs, &mut failures);
+    let mut files: Vec<PathBuf> = dirs
+        .into_iter()
+        .map(|dir| dir.join(file_name))
+        .filter(|file| file.is_file())
+        .collect();
+    files.sort();
+    (files, failures)
+}
+
+fn is_log_file(name: &str) -> bool {
+    matches!(name, SUMMARY_FILE | DETAIL_FILE | ACCURACY_FILE)
+}
+
+/// Directories holding at least one loadgen log. Symlinked directories are
+/// not followed, so a link back up the tree can not recurse forever.
+fn collect_run_dirs(dir: &Path, dirs: &mut Vec<PathBuf>, failures: &mut Vec<ScanFailure>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            failures.push(ScanFailure {
+                path: dir.to_path_buf(),
+                error: e.to_string(),
+            });
+            return;
+        }
+    };
+
+    let mut has_log = false;
+    for entry in entries.flatten() {
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            collect_run_dirs(&entry.path(), dirs, failures);
+        } else if entry.file_name().to_str().is_some_and(is_log_file) {
+            has_log = true;
+        }
+    }
+    if has_log {
+        dirs.push(dir.to_path_buf());
+    }
+}
+
+fn parse_run(
+    key: RunKey,
+    run: Option<String>,
+    dir: &Path,
+    failures: &mut Vec<ScanFailure>,
+) -> RunLogs {
+    let mut fail = |path: PathBuf, error: String| failures.push(ScanFailure { path, error });
+    let mut logs = RunLogs {
+        key,
+        run,
+        dir: dir.to_path_buf(),
+        summary: None,
+        settings: None,
+        issues: Vec::new(),
+        accuracy_entries: None,
+    };
+
+    let summary_file = dir.join(SUMMARY_FILE);
+    if summary_file.is_file() {
+        match parse_log_summary_file(&summary_file.to_string_lossy()) {
+            Ok(doc) => match MlperfSummary::try_from(&doc) {
+                Ok(summary) => logs.summary = Some(summary),
+                Err(e) => fail(summary_file, e.to_string()),
+            },
+            Err(e) => fail(summary_file, e.to_string()),
+        }
+    }
+
+    let detail_file = dir.join(DETAIL_FILE);
+    if detail_file.is_file() {
+        match parse_mlperf_log_detail_file(&detail_file.to_string_lossy()) {
+            Ok(entries) => {
+                logs.issues = issues(&entries);
+                match TestSettings::try_from(entries.as_slice()) {
+                    Ok(settings) => logs.settings = Some(settings),
+                    Err(e) => fail(detail_file, e.to_string()),
+                }
+            }
+            Err(e) => fail(detail_file, e.to_string()),
+        }
+    }
+
+    let accuracy_file = dir.join(ACCURACY_FILE);
+    if accuracy_file.is_file() {
+        // the accuracy log can be large, so count entries without keeping them
+        let count = AccuracyLogReader::open(&accuracy_file.to_string_lossy())
+            .map_err(|e| e.to_string())
+            .and_then(|mut reader| {
+                reader.try_fold(0, |count, record| {
+                    record.map(|_| count + 1).map_err(|e| e.to_string())
+                })
+            });
+        match count {
+            Ok(entries) => logs.accuracy_entries = Some(entries),
+            Err(e) => fail(accuracy_file, e),
+        }
+    }
+
+    logs
+}
@@ -0,0 +1,148 @@
+use crate::format::{cell, csv_field, markdown_cell};
+use crate::log_summary::model::{flatten_dict, Document};
+use crate::log_summary::summary::MlperfSummary;
+use crate::submission::RunKey;
+use indexmap::IndexMap;
+use std::io;
+use std::path::Path;
+
+/// Columns of `summary_row` besides the flattened summary keys.
+pub const DEFAULT_COLUMNS: &[&str] = &[
+    "system",
+    "model",
+    "scenario",
+    "mode",
+    "primary_metric",
+    "primary_metric_value",
+    "validity",
+    "p90_latency_ns",
+    "p99_latency_ns",
+];
+
+/// Rows of string cells under a fixed set of columns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<String>) -> Self {
+        Table {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row, taking the cell of each column from `values`; columns
+    /// missing from `values` are left empty.
+    pub fn push(&mut self, values: &IndexMap<String, String>) {
+        let row = self
+            .columns
+            .iter()
+            .map(|c| values.get(c).cloned().unwrap_or_default())
+            .collect();
+        self.rows.push(row);
+    }
+
+    pub fn write_csv<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let line = |cells: &[String]| {
+            cells
+                .iter()
+                .map(|c| csv_field(c))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        writeln!(writer, "{}", line(&self.columns))?;
+        for row in &self.rows {
+            writeln!(writer, "{}", line(row))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_markdown<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|c| markdown_cell(c)).collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        writeln!(writer, "{}", line(&self.columns))?;
+        writeln!(writer, "|{}", " --- |".repeat(self.columns.len()))?;
+        for row in &self.rows {
+            writeln!(writer, "{}", line(row))?;
+        }
+        Ok(())
+    }
+
+    pub fn write<W: io::Write>(&self, writer: &mut W, format: &str) -> io::Result<()> {
+        match format {
+            "csv" => self.write_csv(writer),
+            "markdown" | "md" => self.write_markdown(writer),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid format. Use 'csv' or 'markdown'.",
+            )),
+        }
+    }
+}
+
+/// One row for a summary log: the `DEFAULT_COLUMNS` plus every flattened
+/// key of `Document::to_dict` (e.g. `test_parameters_used.target_qps`).
+///
+/// `system` and `model` come from the submission layout when `path` follows
+/// it. Otherwise `system` is left empty, as the summary does not name it, and
+/// `model` is the one loadgen logs in `test_parameters_used`.
+pub fn summary_row(path: &Path, doc: &Document) -> IndexMap<String, String> {
+    let flat = flatten_dict(&doc.to_dict());
+    let get = |key: &str| flat.get(key).map(cell).unwrap_or_default();
+
+    let mut row = IndexMap::new();
+    let key = path
+        .parent()
+        .and_then(RunKey::from_dir_tail)
+        .map(|(key, _)| key);
+    row.insert(
+        "system".to_string(),
+        key.as_ref().map(|k| k.system.clone()).unwrap_or_default(),
+    );
+    row.insert(
+        "model".to_string(),
+        key.as_ref()
+            .map(|k| k.model.clone())
+            .unwrap_or_else(|| get("test_parameters_used.model")),
+    );
+    row.insert(
+        "scenario".to_string(),
+        get("mlperf_results_summary.scenario"),
+    );
+    row.insert("mode".to_string(), get("mlperf_results_summary.mode"));
+
+    let metric = MlperfSummary::try_from(doc)
+        .ok()
+        .and_then(|s| s.primary_metric().ok());
+    row.insert(
+        "primary_metric".to_string(),
+        metric.as_ref().map(|m| m.name.clone()).unwrap_or_default(),
+    );
+    row.insert(
+        "primary_metric_value".to_string(),
+        metric.map(|m| m.value.to_string()).unwrap_or_default(),
+    );
+    row.insert(
+        "validity".to_string(),
+        get("mlperf_results_summary.result_is.value"),
+    );
+    row.insert(
+        "p90_latency_ns".to_string(),
+        get("additional_stats.90_00_percentile_latency_ns"),
+    );
+    row.insert(
+        "p99_latency_ns".to_string(),
+        get("additional_stats.99_00_percentile_latency_ns"),
+    );
+
+    row.insert("path".to_string(), path.display().to_string());
+    for (key, value) in &flat {
+        row.insert(key.clone(), cell(value));
+    }
+    row
+}
@@ -0,0 +1,272 @@
+use crate::json_stream::{ElementKind, JsonArrayReader, JsonStreamError};
+use crate::log_summary::summary::{LatencyPercentile, LatencyStats};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// Name of the async events loadgen traces for every sample, from the time
+/// its query was scheduled to the time it completed.
+///
+/// The schema follows loadgen's `logging.h`/`logging.cc`: on completion of a
+/// sample, `loadgen.cc` calls `AsyncLog::TraceSample("Sample", ...)`, which
+/// writes a `b` event at the scheduled time and an `e` event at the
+/// completion time, both with `cat: "default"` and the sample sequence id as
+/// `id`. The begin event carries `sample_seq`, `query_seq`, `sample_idx` and
+/// more in `args`. `ChromeTracer` wraps the events in `{"traceEvents": [...`
+/// and ends the array with a `LastTrace` event, followed by `displayTimeUnit`
+/// and `otherData`. `tests/data/mlperf_log_trace.json` is laid out the same.
+pub const SAMPLE_EVENT: &str = "Sample";
+
+/// Percentiles loadgen reports in the summary.
+pub const PERCENTILES: &[f64] = &[50.0, 90.0, 95.0, 97.0, 99.0, 99.9];
+
+/// An event of `mlperf_log_trace.json` in the Chrome trace event format.
+/// Timestamps and durations are in microseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    #[serde(default)]
+    pub name: String,
+    pub cat: Option<String>,
+    /// Phase: `X` for complete events, `b`/`e` for async begin/end, ...
+    #[serde(default)]
+    pub ph: String,
+    pub ts: Option<f64>,
+    pub dur: Option<f64>,
+    pub pid: Option<u64>,
+    pub tid: Option<u64>,
+    /// Pairs the begin and end of async events, a number or a string.
+    pub id: Option<JsonValue>,
+    #[serde(default)]
+    pub args: Map<String, JsonValue>,
+}
+
+/// Reads the events of a trace log one at a time, from either a bare array
+/// or the `traceEvents` array of a trace object. Iteration stops after the
+/// first error.
+pub struct TraceLogReader<R: BufRead> {
+    inner: JsonArrayReader<R>,
+}
+
+impl<R: BufRead> TraceLogReader<R> {
+    pub fn new(reader: R) -> Self {
+        TraceLogReader {
+            inner: JsonArrayReader::in_object(reader, ElementKind::Event, "traceEvents"),
+        }
+    }
+}
+
+impl TraceLogReader<BufReader<File>> {
+    pub fn open(file_path: &str) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(file_path)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for TraceLogReader<R> {
+    type Item = Result<TraceEvent, JsonStreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_element()
+    }
+}
+
+/// Issue and completion time of a query (or sample) in microseconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryLatency {
+    pub id: String,
+    pub issue_us: f64,
+    pub complete_us: f64,
+}
+
+impl QueryLatency {
+    pub fn latency_ns(&self) -> u64 {
+        ((self.complete_us - self.issue_us) * 1000.0)
+            .round()
+            .max(0.0) as u64
+    }
+}
+
+/// Pairs the events of one name into query latencies: `b`/`e` async events
+/// by their id, and `X` complete events by their duration.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyCollector {
+    name: String,
+    /// Issue time of the async events whose end is not read yet.
+    pending: HashMap<String, f64>,
+    latencies: Vec<QueryLatency>,
+    unmatched_ends: usize,
+}
+
+fn id_key(id: &JsonValue) -> String {
+    match id {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl LatencyCollector {
+    pub fn new(name: &str) -> Self {
+        LatencyCollector {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, event: &TraceEvent) {
+        if event.name != self.name {
+            return;
+        }
+        let (Some(ts), id) = (event.ts, event.id.as_ref().map(id_key)) else {
+            return;
+        };
+        match event.ph.as_str() {
+            "X" => {
+                if let Some(dur) = event.dur {
+                    self.latencies.push(QueryLatency {
+                        id: id.unwrap_or_else(|| self.latencies.len().to_string()),
+                        issue_us: ts,
+                        complete_us: ts + dur,
+                    });
+                }
+            }
+            "b" => {
+                if let Some(id) = id {
+                    self.pending.insert(id, ts);
+                }
+            }
+            "e" => match id.and_then(|id| Some((self.pending.remove(&id)?, id))) {
+                Some((issue_us, id)) => self.latencies.push(QueryLatency {
+                    id,
+                    issue_us,
+                    complete_us: ts,
+                }),
+                None => self.unmatched_ends += 1,
+            },
+            _ => {}
+        }
+    }
+
+    pub fn finish(self) -> TraceLatencies {
+        TraceLatencies {
+            latencies: self.latencies,
+            unmatched: self.pending.len() + self.unmatched_ends,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraceLatencies {
+    /// Latencies in the order the queries completed.
+    pub latencies: Vec<QueryLatency>,
+    /// Begin events without an end and end events without a begin, e.g.
+    /// queries still in flight when the trace was cut.
+    pub unmatched: usize,
+}
+
+impl TraceLatencies {
+    /// Min, max, mean and the `PERCENTILES` of the latencies computed the
+    /// way loadgen does, `None` when there are none.
+    pub fn stats(&self) -> Option<LatencyStats> {
+        let mut latencies: Vec<u64> = self.latencies.iter().map(|l| l.latency_ns()).collect();
+        latencies.sort_unstable();
+        let (&min_ns, &max_ns) = (latencies.first()?, latencies.last()?);
+        let count = latencies.len();
+        let sum: u128 = latencies.iter().map(|&l| l as u128).sum();
+        Some(LatencyStats {
+            min_ns,
+            max_ns,
+            mean_ns: (sum / count as u128) as u64,
+            percentiles: PERCENTILES
+                .iter()
+                .map(|&percentile| {
+                    // loadgen takes the latency at index `count * percentile`
+                    let index = (count as f64 * percentile / 100.0) as usize;
+                    LatencyPercentile {
+                        percentile,
+                        latency_ns: latencies[index.min(count - 1)],
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+pub fn query_latencies<'a>(
+    events: impl IntoIterator<Item = &'a TraceEvent>,
+    name: &str,
+) -> TraceLatencies {
+    let mut collector = LatencyCollector::new(name);
+    for event in events {
+        collector.push(event);
+    }
+    collector.finish()
+}
+
+/// Streams a trace log, collecting the latencies of the events named `name`.
+pub fn trace_latencies_file(
+    file_path: &str,
+    name: &str,
+) -> Result<TraceLatencies, JsonStreamError> {
+    let mut collector = LatencyCollector::new(name);
+    for event in TraceLogReader::open(file_path)? {
+        collector.push(&event?);
+    }
+    Ok(collector.finish())
+}
+
+/// A latency statistic the summary and the trace log disagree on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyMismatch {
+    /// `min`, `max`, `mean` or the percentile, e.g. `99.90 percentile`.
+    pub stat: String,
+    pub summary_ns: u64,
+    pub trace_ns: u64,
+}
+
+impl fmt::Display for LatencyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} latency: summary has {} ns, trace log has {} ns",
+            self.stat, self.summary_ns, self.trace_ns
+        )
+    }
+}
+
+/// Compares the latencies of a summary with the ones computed from the trace
+/// log. Values within `tolerance` (relative, `0.01` for 1%) are considered
+/// equal since the trace only keeps microsecond timestamps.
+pub fn compare_latency_stats(
+    summary: &LatencyStats,
+    trace: &LatencyStats,
+    tolerance: f64,
+) -> Vec<LatencyMismatch> {
+    let mut pairs = vec![
+        ("min".to_string(), summary.min_ns, trace.min_ns),
+        ("max".to_string(), summary.max_ns, trace.max_ns),
+        ("mean".to_string(), summary.mean_ns, trace.mean_ns),
+    ];
+    pairs.extend(summary.percentiles.iter().filter_map(|p| {
+        let trace_ns = trace.percentile(p.percentile)?;
+        Some((
+            format!("{:.2} percentile", p.percentile),
+            p.latency_ns,
+            trace_ns,
+        ))
+    }));
+
+    pairs
+        .into_iter()
+        .filter(|&(_, summary_ns, trace_ns)| {
+            let (a, b) = (summary_ns as f64, trace_ns as f64);
+            (a - b).abs() > tolerance * a.max(b)
+        })
+        .map(|(stat, summary_ns, trace_ns)| LatencyMismatch {
+            stat,
+            summary_ns,
+            trace_ns,
+        })
+        .collect()
+}
@@ -1,6 +1,17 @@
+#![allow(clippy::needless_borrow, clippy::bool_assert_comparison)]
+
 use log::info;
-use mlperf_log_parser::{parse_mlperf_log_detail_file, parse_mlperf_results_file};
+use mlperf_log_parser::cross_check::cross_check_results;
+use mlperf_log_parser::log_detail::{write_log_detail_csv, SettingsError, Severity};
+use mlperf_log_parser::log_summary::summary::Validity;
+use mlperf_log_parser::{
+    cross_check, issues, parse_mlperf_log_detail, parse_mlperf_log_detail_file,
+    parse_mlperf_results_dict, parse_mlperf_results_file, save_log_detail, settings_diff,
+    DetailEvent, DetailLogReader, DetailParseError, FieldMismatch, Issue, ParseError, Scenario,
+    SettingDiff, TestMode, TestSettings,
+};
 use serde_value::Value;
+use std::fs;
 use test_log::test;
 
 #[test]
@@ -149,3 +160,387 @@ fn test_mlperf_log_parser() {
     info!("{:?}", summary);
     assert_eq!(summary.is_ok(), true);
 }
+
+#[test]
+fn test_detail_log_reader() {
+    let reader = DetailLogReader::open("tests/data/mlperf_log_detail.txt").unwrap();
+    let entries = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(entries.len(), 54);
+    assert_eq!(entries[0].key, "loadgen_version");
+    assert_eq!(entries[0].line_no, 1);
+    assert_eq!(entries[53].line_no, 54);
+
+    let text = r#":::MLLOG {"key": "qsl_name", "value": "bye", "time_ms": 0.05}
+not an mllog line
+:::MLLOG{"key": "no_space", "value": 1, "time_ms": 0.05}
+:::MLLOG {"key": "broken", "value":
+:::MLLOG {"key": "sut_name", "value": "PySUT", "time_ms": 0.06}
+"#;
+    let mut reader = DetailLogReader::new(text.as_bytes());
+    assert_eq!(reader.next().unwrap().unwrap().key, "qsl_name");
+    match reader.next().unwrap() {
+        Err(DetailParseError::InvalidJson { line_no, .. }) => assert_eq!(line_no, 4),
+        other => panic!("expected InvalidJson, got {:?}", other),
+    }
+    let entry = reader.next().unwrap().unwrap();
+    assert_eq!(entry.key, "sut_name");
+    assert_eq!(entry.line_no, 5);
+    assert!(reader.next().is_none());
+
+    match parse_mlperf_log_detail(text).unwrap_err() {
+        ParseError::InvalidJson { location, .. } => {
+            assert_eq!(location.line, 4);
+            assert_eq!(location.source, r#":::MLLOG {"key": "broken", "value":"#);
+        }
+        other => panic!("expected InvalidJson, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_detail_events() {
+    let entries = parse_mlperf_log_detail_file("tests/data/mlperf_log_detail.txt").unwrap();
+    let events = entries
+        .iter()
+        .map(DetailEvent::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        events[0],
+        DetailEvent::LoadgenInfo {
+            name: "version".to_string(),
+            value: "4.1 @ 17c35f8b4b".into()
+        }
+    );
+    assert!(events.contains(&DetailEvent::SutName("bye".to_string())));
+    assert!(events.contains(&DetailEvent::RequestedSetting {
+        name: "min_duration_ms".to_string(),
+        value: 10000.into()
+    }));
+    assert!(events.contains(&DetailEvent::EffectiveSetting {
+        name: "scenario".to_string(),
+        value: "SingleStream".into()
+    }));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, DetailEvent::Unknown { key, .. } if key.starts_with("effective_"))));
+
+    let text = r#":::MLLOG {"key": "result_validity", "value": "INVALID", "time_ms": 1.0, "metadata": {"is_error": false, "is_warning": false}}
+:::MLLOG {"key": "result_min_duration_met", "value": false, "time_ms": 1.0}
+:::MLLOG {"key": "result_samples_per_second", "value": 311.39, "time_ms": 1.0}
+:::MLLOG {"key": "power_begin", "value": "10-22-2024 10:22:30.123", "time_ms": 1.0}
+:::MLLOG {"key": "error_invalid_config", "value": "Multiple conf files", "time_ms": 1.0, "metadata": {"is_error": true, "is_warning": false}}
+:::MLLOG {"key": "generated_query_count", "value": 1024, "time_ms": 1.0}
+:::MLLOG {"key": "sut_name", "value": 1, "time_ms": 1.0}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    let events: Vec<_> = entries.iter().map(DetailEvent::try_from).collect();
+    assert_eq!(
+        events[0],
+        Ok(DetailEvent::ResultValidity(Validity::Invalid))
+    );
+    assert_eq!(
+        events[1],
+        Ok(DetailEvent::ResultCheck {
+            name: "min_duration_met".to_string(),
+            met: false
+        })
+    );
+    assert_eq!(
+        events[2],
+        Ok(DetailEvent::ResultMetric {
+            name: "samples_per_second".to_string(),
+            value: 311.39
+        })
+    );
+    assert_eq!(
+        events[3],
+        Ok(DetailEvent::PowerBegin(
+            "10-22-2024 10:22:30.123".to_string()
+        ))
+    );
+    assert_eq!(
+        events[4],
+        Ok(DetailEvent::Error {
+            key: "error_invalid_config".to_string(),
+            message: "Multiple conf files".to_string()
+        })
+    );
+    assert_eq!(
+        events[5],
+        Ok(DetailEvent::Unknown {
+            key: "generated_query_count".to_string(),
+            value: 1024.into()
+        })
+    );
+    let error = events[6].clone().unwrap_err();
+    assert_eq!(error.key, "sut_name");
+    assert_eq!(error.line_no, 7);
+}
+
+#[test]
+fn test_settings_diff() {
+    let entries = parse_mlperf_log_detail_file("tests/data/mlperf_log_detail.txt").unwrap();
+    assert_eq!(settings_diff(&entries), vec![]);
+
+    let text = r#":::MLLOG {"key": "requested_scenario", "value": "Server", "time_ms": 0.1}
+:::MLLOG {"key": "requested_min_duration_ms", "value": 600000, "time_ms": 0.1}
+:::MLLOG {"key": "requested_server_target_qps", "value": 100.0, "time_ms": 0.1}
+:::MLLOG {"key": "requested_min_query_count", "value": 100, "time_ms": 0.1}
+:::MLLOG {"key": "effective_scenario", "value": "Server", "time_ms": 0.2}
+:::MLLOG {"key": "effective_min_duration_ms", "value": 10000, "time_ms": 0.2}
+:::MLLOG {"key": "effective_min_query_count", "value": 100.0, "time_ms": 0.2}
+:::MLLOG {"key": "effective_target_qps", "value": 100, "time_ms": 0.2}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    let diffs = settings_diff(&entries);
+    assert_eq!(
+        diffs,
+        vec![SettingDiff {
+            name: "min_duration_ms".to_string(),
+            effective_name: "min_duration_ms".to_string(),
+            requested: 600000.into(),
+            effective: 10000.into(),
+        }]
+    );
+    assert_eq!(
+        diffs[0].to_string(),
+        "min_duration_ms: requested 600000, effective 10000"
+    );
+
+    // scenario specific settings are compared with the ones they apply to
+    let text = fs::read_to_string("tests/data/mlperf_log_detail.txt")
+        .unwrap()
+        .replace("SingleStream", "Server")
+        .replace(
+            r#""requested_single_stream_expected_latency_ns", "value": 1e+06"#,
+            r#""requested_server_target_qps", "value": 500"#,
+        );
+    let entries = parse_mlperf_log_detail(&text).unwrap();
+    let diffs = settings_diff(&entries);
+    assert_eq!(
+        diffs,
+        vec![SettingDiff {
+            name: "server_target_qps".to_string(),
+            effective_name: "target_qps".to_string(),
+            requested: 500.into(),
+            effective: 1000.into(),
+        }]
+    );
+    assert_eq!(
+        diffs[0].to_string(),
+        "server_target_qps: requested 500, effective target_qps 1000"
+    );
+}
+
+#[test]
+fn test_issues() {
+    let entries = parse_mlperf_log_detail_file("tests/data/mlperf_log_detail.txt").unwrap();
+    assert!(issues(&entries).is_empty());
+
+    let text = r#":::MLLOG {"key": "sut_name", "value": "bye", "time_ms": 0.05, "metadata": {"is_error": false, "is_warning": false}}
+:::MLLOG {"key": "warning_generic_message", "value": "Setting min_duration_ms to 0", "time_ms": 1.5, "metadata": {"is_error": false, "is_warning": true, "file": "test_settings_internal.cc", "line_no": 680}}
+:::MLLOG {"key": "error_invalid_config", "value": "Multiple conf files", "time_ms": 2.25, "metadata": {"is_error": true, "is_warning": false, "file": "test_settings_internal.cc", "line_no": 706}}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    let issues = issues(&entries);
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[0].severity, Severity::Warning);
+    assert_eq!(
+        issues[1],
+        Issue {
+            severity: Severity::Error,
+            key: "error_invalid_config".to_string(),
+            message: "Multiple conf files".to_string(),
+            time_ms: 2.25,
+            file: Some("test_settings_internal.cc".to_string()),
+            source_line: Some(706),
+            line_no: 3,
+        }
+    );
+    assert_eq!(
+        issues[1].to_string(),
+        "error: [2.25ms] error_invalid_config: Multiple conf files (test_settings_internal.cc:706)"
+    );
+}
+
+#[test]
+fn test_test_settings() {
+    let entries = parse_mlperf_log_detail_file("tests/data/mlperf_log_detail.txt").unwrap();
+    let settings = TestSettings::try_from(entries.as_slice()).unwrap();
+    assert_eq!(settings.scenario, Scenario::SingleStream);
+    assert_eq!(settings.mode, TestMode::AccuracyOnly);
+    assert_eq!(settings.samples_per_query, Some(1));
+    assert_eq!(settings.target_qps, Some(1000.0));
+    assert_eq!(settings.target_latency_ns, Some(0));
+    assert_eq!(settings.target_latency_percentile, Some(0.9));
+    assert_eq!(settings.min_duration_ms, Some(10000));
+    assert_eq!(settings.min_query_count, Some(100));
+    assert_eq!(settings.qsl_rng_seed, Some(0));
+    assert_eq!(settings.print_timestamps, Some(false));
+    assert_eq!(settings.performance_issue_unique, Some(false));
+    // the fixture stops before these entries
+    assert_eq!(settings.performance_issue_same, None);
+    assert_eq!(settings.performance_sample_count, None);
+    assert!(settings.other.is_empty());
+
+    let text = r#":::MLLOG {"key": "effective_scenario", "value": "Server", "time_ms": 0.1}
+:::MLLOG {"key": "effective_test_mode", "value": "PerformanceOnly", "time_ms": 0.1}
+:::MLLOG {"key": "effective_sample_index_rng_seed", "value": 10688027786191513374, "time_ms": 0.1}
+:::MLLOG {"key": "effective_use_token_latencies", "value": true, "time_ms": 0.1}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    let settings = TestSettings::try_from(entries.as_slice()).unwrap();
+    assert_eq!(settings.scenario, Scenario::Server);
+    assert_eq!(settings.mode, TestMode::PerformanceOnly);
+    assert_eq!(settings.sample_index_rng_seed, Some(10688027786191513374));
+    assert_eq!(settings.other["use_token_latencies"], true);
+
+    let entries = parse_mlperf_log_detail(&text.replace("\"Server\"", "\"Batch\"")).unwrap();
+    assert_eq!(
+        TestSettings::try_from(entries.as_slice()),
+        Err(SettingsError::InvalidType {
+            field: "scenario".to_string(),
+            expected: "a known name",
+            found: "Batch".into()
+        })
+    );
+
+    let entries = parse_mlperf_log_detail(text.lines().nth(1).unwrap()).unwrap();
+    assert_eq!(
+        TestSettings::try_from(entries.as_slice()),
+        Err(SettingsError::MissingField {
+            field: "scenario".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_emit_conf() {
+    let entries = parse_mlperf_log_detail_file("tests/data/mlperf_log_detail.txt").unwrap();
+    let settings = TestSettings::try_from(entries.as_slice()).unwrap();
+    let mut conf = Vec::new();
+    settings.write_conf("resnet50", &mut conf).unwrap();
+    assert_eq!(
+        String::from_utf8(conf).unwrap(),
+        "\
+resnet50.SingleStream.mode = 1
+resnet50.SingleStream.target_latency = 1
+resnet50.SingleStream.target_latency_percentile = 0.9
+resnet50.SingleStream.max_async_queries = 1
+resnet50.SingleStream.min_duration = 10000
+resnet50.SingleStream.max_duration = 0
+resnet50.SingleStream.min_query_count = 100
+resnet50.SingleStream.max_query_count = 0
+resnet50.SingleStream.qsl_rng_seed = 0
+resnet50.SingleStream.sample_index_rng_seed = 0
+resnet50.SingleStream.schedule_rng_seed = 0
+resnet50.SingleStream.accuracy_log_rng_seed = 0
+resnet50.SingleStream.accuracy_log_probability = 0
+resnet50.SingleStream.accuracy_log_sampling_target = 0
+resnet50.SingleStream.print_timestamps = 0
+resnet50.SingleStream.performance_issue_unique = 0
+"
+    );
+
+    let text = r#":::MLLOG {"key": "effective_scenario", "value": "Server", "time_ms": 0.1}
+:::MLLOG {"key": "effective_test_mode", "value": "PerformanceOnly", "time_ms": 0.1}
+:::MLLOG {"key": "effective_target_qps", "value": 250.5, "time_ms": 0.1}
+:::MLLOG {"key": "effective_target_latency_ns", "value": 15000000, "time_ms": 0.1}
+:::MLLOG {"key": "effective_performance_sample_count", "value": 1024, "time_ms": 0.1}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    let settings = TestSettings::try_from(entries.as_slice()).unwrap();
+    assert_eq!(
+        settings.conf_entries(),
+        vec![
+            ("mode", "2".to_string()),
+            ("target_qps", "250.5".to_string()),
+            ("target_latency", "15".to_string()),
+            ("performance_sample_count_override", "1024".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_cross_check() {
+    let summary = parse_mlperf_results_dict("tests/data/mlperf_log_summary.txt").unwrap();
+    // sections stay in the order of the log
+    assert_eq!(
+        summary.keys().next().map(|k| k.as_str()),
+        Some("mlperf_results_summary")
+    );
+    let text = r#":::MLLOG {"key": "effective_scenario", "value": "Offline", "time_ms": 0.1}
+:::MLLOG {"key": "effective_test_mode", "value": "Performance", "time_ms": 0.1}
+:::MLLOG {"key": "result_validity", "value": "VALID", "time_ms": 9.0}
+:::MLLOG {"key": "result_samples_per_second", "value": 1234.5612, "time_ms": 9.0}
+:::MLLOG {"key": "result_min_latency_ns", "value": 123456, "time_ms": 9.0}
+:::MLLOG {"key": "result_90.00_percentile_latency_ns", "value": 456789, "time_ms": 9.0}
+:::MLLOG {"key": "result_99.90_percentile_latency_ns", "value": 890123, "time_ms": 9.0}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    assert_eq!(cross_check_results(&summary, &entries), vec![]);
+
+    let text = text
+        .replace("\"VALID\"", "\"INVALID\"")
+        .replace("890123", "890124");
+    let entries = parse_mlperf_log_detail(&text).unwrap();
+    assert_eq!(
+        cross_check_results(&summary, &entries),
+        vec![
+            FieldMismatch {
+                field: "mlperf_results_summary.result_is".to_string(),
+                detail_key: "result_validity".to_string(),
+                summary: "VALID".to_string(),
+                detail: "INVALID".to_string(),
+            },
+            FieldMismatch {
+                field: "additional_stats.99_90_percentile_latency_ns".to_string(),
+                detail_key: "result_99.90_percentile_latency_ns".to_string(),
+                summary: "890123".to_string(),
+                detail: "890124".to_string(),
+            },
+        ]
+    );
+
+    // the fixtures come from different runs
+    let mismatches = cross_check(
+        "tests/data/mlperf_log_summary.txt",
+        "tests/data/mlperf_log_detail.txt",
+    )
+    .unwrap();
+    let fields: Vec<&str> = mismatches.iter().map(|m| m.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        vec![
+            "mlperf_results_summary.scenario",
+            "mlperf_results_summary.mode"
+        ]
+    );
+}
+
+#[test]
+fn test_save_log_detail_csv() {
+    let mut output = Vec::new();
+    save_log_detail("tests/data/mlperf_log_detail.txt", &mut output, "csv").unwrap();
+    let csv = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "key,value,time_ms,event_type,metadata.is_error,metadata.is_warning,metadata.file,metadata.line_no"
+    );
+    assert_eq!(
+        lines[1],
+        "loadgen_version,4.1 @ 17c35f8b4b,0.032262,POINT_IN_TIME,false,false,/usr/home/git/loadgen/inference/loadgen/version.cc,54"
+    );
+
+    let text = r#":::MLLOG {"key": "loadgen_file_sha1", "value": {"/a.cc": "01", "/b.h": "02"}, "time_ms": 0.5, "event_type": "POINT_IN_TIME", "metadata": {"pid": 7}}
+"#;
+    let entries = parse_mlperf_log_detail(text).unwrap();
+    let mut output = Vec::new();
+    write_log_detail_csv(&entries, &mut output, &["pid"]).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "key,value,time_ms,event_type,metadata.pid\n\
+loadgen_file_sha1,\"{\"\"/a.cc\"\":\"\"01\"\",\"\"/b.h\"\":\"\"02\"\"}\",0.5,POINT_IN_TIME,7\n"
+    );
+}
@@ -0,0 +1,147 @@
+use mlperf_log_parser::accuracy_log::{
+    decode_hex, save_accuracy_log, write_accuracy_log, AccuracyLogError, DataType, DecodeError,
+};
+use mlperf_log_parser::json_stream::{ElementKind, JsonStreamError};
+use mlperf_log_parser::{AccuracyLogReader, AccuracyRecord};
+use test_log::test;
+
+fn record(data: &str) -> AccuracyRecord {
+    AccuracyRecord {
+        seq_id: 0,
+        qsl_idx: 0,
+        data: data.to_string(),
+    }
+}
+
+#[test]
+fn test_accuracy_log_reader() {
+    let records: Vec<AccuracyRecord> =
+        AccuracyLogReader::open("tests/data/mlperf_log_accuracy.json")
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records[0],
+        AccuracyRecord {
+            seq_id: 0,
+            qsl_idx: 17,
+            data: "0000803F00002040".to_string(),
+        }
+    );
+    assert_eq!(records[2].qsl_idx, 42);
+
+    let empty = AccuracyLogReader::new("[\n]\n".as_bytes()).count();
+    assert_eq!(empty, 0);
+
+    // a log cut short by a crashed run
+    let text = r#"[
+{ "seq_id" : 0, "qsl_idx" : 1, "data" : "00" },
+{ "seq_id" : 1, "qsl_idx" : 2, "data" : "01" }"#;
+    let results: Vec<_> = AccuracyLogReader::new(text.as_bytes()).collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_ok());
+    match &results[2] {
+        Err(
+            e @ JsonStreamError::Malformed {
+                kind: ElementKind::Record,
+                index: 2,
+                ..
+            },
+        ) => assert_eq!(
+            e.to_string(),
+            "malformed accuracy log (record 2): expected ',' or ']', found end of file"
+        ),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let text = r#"[{ "seq_id" : 0, "qsl_idx" : 1 }]"#;
+    let results: Vec<_> = AccuracyLogReader::new(text.as_bytes()).collect();
+    assert!(matches!(
+        results.as_slice(),
+        [Err(JsonStreamError::InvalidJson { index: 0, .. })]
+    ));
+}
+
+#[test]
+fn test_accuracy_record_decode() {
+    assert_eq!(decode_hex("00ff7F").unwrap(), vec![0x00, 0xff, 0x7f]);
+    assert_eq!(decode_hex("ABC"), Err(DecodeError::OddLength { len: 3 }));
+    assert_eq!(
+        decode_hex("0G"),
+        Err(DecodeError::InvalidHex {
+            position: 1,
+            found: 'G'
+        })
+    );
+
+    assert_eq!(record("0000803F00002040").f32s().unwrap(), vec![1.0, 2.5]);
+    assert_eq!(record("FFFFFFFF02000000").i32s().unwrap(), vec![-1, 2]);
+    assert_eq!(record("2A00000000000000").i64s().unwrap(), vec![42]);
+    assert_eq!(record("").f32s().unwrap(), Vec::<f32>::new());
+    assert_eq!(
+        record("2A000000").i64s(),
+        Err(DecodeError::Misaligned {
+            len: 4,
+            element_size: 8
+        })
+    );
+}
+
+#[test]
+fn test_write_accuracy_log() {
+    let mut output = Vec::new();
+    let count = save_accuracy_log(
+        "tests/data/mlperf_log_accuracy.json",
+        &mut output,
+        "jsonl",
+        DataType::F32,
+    )
+    .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"{"seq_id":0,"qsl_idx":17,"data":[1.0,2.5]}
+{"seq_id":1,"qsl_idx":3,"data":[-2.0]}
+{"seq_id":2,"qsl_idx":42,"data":[]}
+"#
+    );
+
+    let mut output = Vec::new();
+    save_accuracy_log(
+        "tests/data/mlperf_log_accuracy.json",
+        &mut output,
+        "csv",
+        DataType::U8,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "seq_id,qsl_idx,data\n0,17,0 0 128 63 0 0 32 64\n1,3,0 0 0 192\n2,42,\n"
+    );
+
+    let text = r#"[{ "seq_id" : 5, "qsl_idx" : 1, "data" : "000000" }]"#;
+    let err = write_accuracy_log(
+        AccuracyLogReader::new(text.as_bytes()),
+        &mut Vec::new(),
+        "jsonl",
+        DataType::I32,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid data (seq_id 5): 3 bytes is not a multiple of the element size (4)"
+    );
+
+    let err = write_accuracy_log(
+        AccuracyLogReader::new(&text.as_bytes()[..10]),
+        &mut Vec::new(),
+        "jsonl",
+        DataType::U8,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        AccuracyLogError::Read(JsonStreamError::InvalidJson { index: 0, .. })
+    ));
+}
@@ -0,0 +1,146 @@
+use mlperf_log_parser::conf::{Conf, ConfMismatch};
+use mlperf_log_parser::{parse_mlperf_log_detail_file, ParseError, Scenario, TestSettings};
+use test_log::test;
+
+const CONF: &str = r#"
+# The format of this config file is 'key = value'.
+*.*.mode = 2
+*.*.min_duration = 600000
+*.SingleStream.target_latency = 10
+*.Offline.min_query_count = 1
+resnet50.*.min_query_count = 1024
+resnet50.Offline.target_qps = 1000.0  # from the last run
+resnet50.Offline.target_qps = 2000
+"#;
+
+#[test]
+fn test_conf_parse() {
+    let conf = Conf::parse(CONF).unwrap();
+    assert_eq!(conf.files.len(), 1);
+    assert_eq!(conf.entries().count(), 7);
+    let entry = conf.entries().nth(5).unwrap();
+    assert_eq!(entry.model, "resnet50");
+    assert_eq!(entry.scenario, "Offline");
+    assert_eq!(entry.key, "target_qps");
+    assert_eq!(entry.value, "1000.0");
+    assert_eq!(entry.line_no, 8);
+}
+
+#[test]
+fn test_conf_precedence() {
+    let conf = Conf::parse(CONF).unwrap();
+    let value = |model, scenario, key| conf.get(model, scenario, key).map(|e| e.value.as_str());
+
+    // the last of two identical keys wins
+    assert_eq!(
+        value("resnet50", Scenario::Offline, "target_qps"),
+        Some("2000")
+    );
+    // *.Scenario beats model.*
+    assert_eq!(
+        value("resnet50", Scenario::Offline, "min_query_count"),
+        Some("1")
+    );
+    assert_eq!(
+        value("resnet50", Scenario::Server, "min_query_count"),
+        Some("1024")
+    );
+    assert_eq!(value("bert", Scenario::Server, "min_query_count"), None);
+    assert_eq!(value("bert", Scenario::Server, "mode"), Some("2"));
+
+    let resolved = conf.resolve("resnet50", Scenario::SingleStream);
+    let keys: Vec<&str> = resolved.keys().map(|k| k.as_str()).collect();
+    assert_eq!(
+        keys,
+        vec!["mode", "min_duration", "target_latency", "min_query_count"]
+    );
+
+    let mut user = Conf::parse("resnet50.Offline.target_qps = 3000\n").unwrap();
+    let mut merged = conf.clone();
+    merged.extend(user.clone());
+    assert_eq!(
+        merged
+            .get("resnet50", Scenario::Offline, "target_qps")
+            .unwrap()
+            .value,
+        "3000"
+    );
+    user.extend(conf);
+    assert_eq!(
+        user.get("resnet50", Scenario::Offline, "target_qps")
+            .unwrap()
+            .value,
+        "2000"
+    );
+
+    // a later file wins even with a less specific line, as loadgen applies
+    // the files one after the other
+    let mut merged = Conf::parse("resnet50.Server.target_latency = 15\n").unwrap();
+    merged.extend(Conf::parse("*.Server.target_latency = 20\n").unwrap());
+    assert_eq!(
+        merged
+            .get("resnet50", Scenario::Server, "target_latency")
+            .unwrap()
+            .value,
+        "20"
+    );
+    assert_eq!(
+        merged.resolve("resnet50", Scenario::Server)["target_latency"].value,
+        "20"
+    );
+}
+
+#[test]
+fn test_conf_invalid_line() {
+    let error = Conf::parse("*.*.mode = 2\nresnet50.Offline = 1\n").unwrap_err();
+    match &error {
+        ParseError::InvalidConf {
+            location, message, ..
+        } => {
+            assert_eq!(location.line, 2);
+            assert_eq!(location.column, 1);
+            assert_eq!(message, "expected `model.Scenario.key`");
+        }
+        other => panic!("expected InvalidConf, got {:?}", other),
+    }
+    assert_eq!(
+        error.to_string(),
+        "\
+error: invalid config line
+ --> <input>:2:1
+  |
+2 | resnet50.Offline = 1
+  | ^^^^^^^^^^^^^^^^^^^^ expected `model.Scenario.key`
+  |
+  = hint: config lines look like `model.Scenario.key = value`"
+    );
+    assert!(Conf::parse("*.*.mode =\n").is_err());
+}
+
+#[test]
+fn test_conf_check() {
+    let entries = parse_mlperf_log_detail_file("tests/data/mlperf_log_detail.txt").unwrap();
+    let settings = TestSettings::try_from(entries.as_slice()).unwrap();
+
+    // effective: AccuracyOnly, 1ms expected latency, 10000ms min duration, 100 queries
+    let conf = Conf::parse(
+        "\
+*.*.mode = 1
+*.SingleStream.target_latency = 1
+*.*.min_duration = 600000
+resnet50.*.min_query_count = 100
+*.*.performance_sample_count_override = 0
+*.*.test05 = 0
+",
+    )
+    .unwrap();
+    let mismatches = conf.check("resnet50", &settings);
+    assert_eq!(mismatches.len(), 1);
+    let ConfMismatch { entry, effective } = &mismatches[0];
+    assert_eq!(entry.key, "min_duration");
+    assert_eq!(effective, "10000");
+    assert_eq!(
+        mismatches[0].to_string(),
+        "line 3: *.*.min_duration = 600000, but loadgen ran with 10000"
+    );
+}
@@ -0,0 +1,156 @@
+use mlperf_log_parser::log_summary::diff::{
+    diff_summaries, diff_summary_files, missing_watched_keys, parse_threshold, regressions,
+    unknown_thresholds, Direction, Threshold, ValueChange,
+};
+use mlperf_log_parser::parse_log_summary;
+use serde_value::Value;
+use test_log::test;
+
+const OLD: &str = "================================================
+MLPerf Results Summary
+================================================
+Scenario : Offline
+Samples per second: 1000
+Result is : VALID
+
+================================================
+Additional Stats
+================================================
+99.00 percentile latency (ns)   : 200
+
+";
+
+const NEW: &str = "================================================
+MLPerf Results Summary
+================================================
+Scenario : Offline
+Samples per second: 975.5
+Result is : INVALID
+
+================================================
+Additional Stats
+================================================
+99.00 percentile latency (ns)   : 200
+
+";
+
+const SLOWER: &str = "================================================
+MLPerf Results Summary
+================================================
+Scenario : Offline
+Samples per second: 975.5
+Result is : VALID
+
+================================================
+Additional Stats
+================================================
+99.00 percentile latency (ns)   : 212
+
+";
+
+#[test]
+fn test_diff_summaries() {
+    let old = parse_log_summary(OLD).unwrap().to_dict();
+    let new = parse_log_summary(NEW).unwrap().to_dict();
+
+    let changes = diff_summaries(&old, &new);
+    assert_eq!(
+        changes,
+        vec![
+            ValueChange {
+                key: "mlperf_results_summary.samples_per_second".to_string(),
+                old: Some(Value::F64(1000.0)),
+                new: Some(Value::F64(975.5)),
+            },
+            ValueChange {
+                key: "mlperf_results_summary.result_is".to_string(),
+                old: Some(Value::String("VALID".to_string())),
+                new: Some(Value::String("INVALID".to_string())),
+            },
+        ]
+    );
+    assert_eq!(changes[0].absolute(), Some(-24.5));
+    assert_eq!(changes[0].relative(), Some(-0.0245));
+    assert_eq!(
+        changes[0].to_string(),
+        "mlperf_results_summary.samples_per_second: 1000 -> 975.5 (-24.5, -2.45%)"
+    );
+    assert_eq!(changes[1].relative(), None);
+    assert_eq!(
+        changes[1].to_string(),
+        "mlperf_results_summary.result_is: VALID -> INVALID"
+    );
+
+    let changes = diff_summary_files(
+        "tests/data/mlperf_log_summary.txt",
+        "tests/data/mlperf_log_summary_singlestream.txt",
+    )
+    .unwrap();
+    let missing = changes
+        .iter()
+        .find(|c| c.key == "mlperf_results_summary.samples_per_second")
+        .unwrap();
+    assert_eq!(missing.new, None);
+    assert_eq!(
+        missing.to_string(),
+        "mlperf_results_summary.samples_per_second: 1234.56 -> (missing)"
+    );
+}
+
+#[test]
+fn test_diff_thresholds() {
+    let old = parse_log_summary(OLD).unwrap().to_dict();
+    let new = parse_log_summary(SLOWER).unwrap().to_dict();
+    let changes = diff_summaries(&old, &new);
+
+    let throughput = parse_threshold("samples_per_second=2%", Direction::Decrease).unwrap();
+    let latency = parse_threshold("99_00_percentile_latency_ns=5", Direction::Increase).unwrap();
+    assert_eq!(
+        latency,
+        Threshold::new("99_00_percentile_latency_ns", Direction::Increase, 5.0)
+    );
+    assert!(throughput.matches("mlperf_results_summary.samples_per_second"));
+    assert!(!throughput.matches("mlperf_results_summary.completed_samples_per_second"));
+
+    let found = regressions(&changes, &[throughput.clone(), latency.clone()]);
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].threshold, throughput);
+    assert_eq!(
+        found[1].to_string(),
+        "additional_stats.99_00_percentile_latency_ns: 200 -> 212 (+12, +6.00%), \
+         more than the allowed 5% increase"
+    );
+
+    // a throughput increase or a smaller latency increase is fine
+    let loose = [
+        Threshold::new("samples_per_second", Direction::Increase, 2.0),
+        Threshold::new("99_00_percentile_latency_ns", Direction::Increase, 10.0),
+    ];
+    assert!(regressions(&changes, &loose).is_empty());
+
+    // a misspelled key matches nothing and is rejected rather than ignored
+    let typo = Threshold::new("samples_per_secnd", Direction::Decrease, 2.0);
+    let all = [throughput.clone(), latency, typo.clone()];
+    assert_eq!(unknown_thresholds(&old, &new, &all), vec![&typo]);
+
+    // a watched key missing on one side is a regression
+    let new = parse_log_summary(&OLD.replace("Samples", "Queries"))
+        .unwrap()
+        .to_dict();
+    let changes = diff_summaries(&old, &new);
+    let watched = [throughput];
+    assert!(unknown_thresholds(&old, &new, &watched).is_empty());
+    // a missing key is not a regression but is reported on its own
+    assert!(regressions(&changes, &watched).is_empty());
+    let missing = missing_watched_keys(&changes, &watched);
+    assert_eq!(missing.len(), 1);
+    assert_eq!(
+        missing[0].to_string(),
+        "mlperf_results_summary.samples_per_second: 1000 -> (missing)"
+    );
+    let unwatched = [Threshold::new("target_qps", Direction::Increase, 5.0)];
+    assert!(missing_watched_keys(&changes, &unwatched).is_empty());
+
+    assert!(parse_threshold("samples_per_second", Direction::Decrease).is_err());
+    assert!(parse_threshold("samples_per_second=abc", Direction::Decrease).is_err());
+}
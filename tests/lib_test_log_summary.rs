@@ -1,8 +1,17 @@
 use log::debug;
+use mlperf_log_parser::log_summary::early_stopping::{EarlyStoppingResult, PercentileEstimate};
 use mlperf_log_parser::log_summary::grammar::LogSummaryParser;
 use mlperf_log_parser::log_summary::lexer::Lexer;
-use serde_json;
-use serde_yaml;
+use mlperf_log_parser::log_summary::model::{
+    self, Dict, DictOptions, DictValue, DuplicateKey, DuplicateKeyPolicy, NumberType, TimeUnit,
+    UnitMode,
+};
+use mlperf_log_parser::log_summary::report;
+use mlperf_log_parser::log_summary::summary::{MetricUnit, MlperfSummary, SummaryError, Validity};
+use mlperf_log_parser::{
+    parse_log_summary, parse_log_summary_file, save_summary, ParseError, Scenario, TestMode,
+};
+use serde_value::Value;
 use test_log::test;
 
 #[test]
@@ -78,3 +87,551 @@ No errors encountered during test.
     debug!("{}", serde_json::to_string_pretty(&dict).unwrap());
     debug!("{}", serde_yaml::to_string(&dict).unwrap());
 }
+
+#[test]
+fn test_summary_model() {
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary.txt").unwrap();
+    let summary = MlperfSummary::try_from(&doc).unwrap();
+    debug!("{:?}", summary);
+
+    assert_eq!(summary.sut_name, "Sample System");
+    assert_eq!(summary.scenario, Scenario::Offline);
+    assert_eq!(summary.mode, TestMode::PerformanceOnly);
+    assert_eq!(summary.validity, Validity::Valid);
+    assert!(summary.constraints.min_duration);
+    assert!(summary.constraints.min_queries);
+    assert_eq!(summary.constraints.early_stopping, Some(true));
+    assert_eq!(summary.constraints.performance_constraints, None);
+    assert!(summary.metrics.contains_key("samples_per_second"));
+    let latency = summary.latency.unwrap();
+    assert_eq!(latency.min_ns, 123456);
+    assert_eq!(latency.mean_ns, 345678);
+    assert_eq!(latency.percentiles.len(), 6);
+    assert_eq!(latency.percentile(99.9), Some(890123));
+    assert_eq!(
+        summary.test_parameters.unwrap()["dataset"],
+        Value::String("ImageNet".to_string())
+    );
+}
+
+#[test]
+fn test_summary_model_optional_sections() {
+    let input = r###"================================================
+MLPerf Results Summary
+================================================
+SUT name : Sample System
+Scenario : Offline
+Mode     : AccuracyOnly
+Samples per second: 1234.56
+Result is : VALID
+  Min duration satisfied : Yes
+  Min queries satisfied : Yes
+
+"###;
+
+    let doc = parse_log_summary(input).unwrap();
+    let summary = MlperfSummary::try_from(&doc).unwrap();
+    assert_eq!(summary.mode, TestMode::AccuracyOnly);
+    assert_eq!(summary.latency, None);
+    assert_eq!(summary.test_parameters, None);
+
+    let doc = parse_log_summary(&input.replace("AccuracyOnly", "Unknown")).unwrap();
+    assert!(matches!(
+        MlperfSummary::try_from(&doc).unwrap_err(),
+        SummaryError::InvalidType { field, .. } if field == "mlperf_results_summary.mode"
+    ));
+}
+
+#[test]
+fn test_summary_model_missing_field() {
+    let input = r###"================================================
+MLPerf Results Summary
+================================================
+SUT name : Sample System
+Scenario : Offline
+Mode     : PerformanceOnly
+Samples per second: 1234.56
+Result is : VALID
+  Min queries satisfied : Yes
+
+================================================
+Additional Stats
+================================================
+Min latency (ns)                : 123456
+Max latency (ns)                : 789012
+Mean latency (ns)               : 345678
+
+================================================
+Test Parameters Used
+================================================
+samples_per_query : 13368
+
+"###;
+
+    let doc = parse_log_summary(input).unwrap();
+    assert_eq!(
+        MlperfSummary::try_from(&doc).unwrap_err(),
+        SummaryError::MissingField {
+            field: "mlperf_results_summary.result_is.details.min_duration_satisfied".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_summary_primary_metric() {
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary.txt").unwrap();
+    let metric = MlperfSummary::try_from(&doc)
+        .unwrap()
+        .primary_metric()
+        .unwrap();
+    assert_eq!(metric.name, "samples_per_second");
+    assert_eq!(metric.unit, MetricUnit::SamplesPerSecond);
+
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary_singlestream.txt").unwrap();
+    let mut summary = MlperfSummary::try_from(&doc).unwrap();
+    assert_eq!(summary.scenario, Scenario::SingleStream);
+    let metric = summary.primary_metric().unwrap();
+    assert_eq!(metric.name, "90th_percentile_latency_ns");
+    assert_eq!(metric.value, 3211111.0);
+    assert_eq!(metric.unit, MetricUnit::Nanoseconds);
+
+    summary.scenario = Scenario::MultiStream;
+    assert_eq!(
+        summary.primary_metric().unwrap_err(),
+        SummaryError::MissingField {
+            field: "mlperf_results_summary.99th_percentile_latency_ns".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_early_stopping_result() {
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary_singlestream.txt").unwrap();
+    let summary = MlperfSummary::try_from(&doc).unwrap();
+    let early_stopping = summary.early_stopping.unwrap();
+
+    assert_eq!(early_stopping.min_queries, Some(64));
+    assert_eq!(early_stopping.processed_queries, Some(193472));
+    assert_eq!(early_stopping.discarded_queries, Some(18953));
+    assert_eq!(
+        early_stopping.estimates,
+        vec![
+            PercentileEstimate {
+                percentile: 90.0,
+                latency_ns: 3214545
+            },
+            PercentileEstimate {
+                percentile: 99.0,
+                latency_ns: 3562101
+            },
+        ]
+    );
+    assert_eq!(early_stopping.latency_bound_ns(), Some(3562101));
+    assert!(early_stopping.notes.is_empty());
+
+    let dict = serde_json::to_value(doc.to_dict()).unwrap();
+    assert_eq!(
+        dict["mlperf_results_summary"]["early_stopping_result"]["discarded_queries"],
+        18953
+    );
+}
+
+#[test]
+fn test_early_stopping_result_server() {
+    let result = EarlyStoppingResult::parse([
+        " * Run unsuccessful.",
+        " * Processed 9052 queries.",
+        " * Would need to run at least 3392 more queries,",
+    ]);
+    assert_eq!(result.run_successful, Some(false));
+    assert_eq!(result.processed_queries, Some(9052));
+    assert_eq!(result.required_queries, Some(3392));
+    assert_eq!(result.latency_bound_ns(), None);
+}
+
+#[test]
+fn test_parse_errors() {
+    // section separator right after the entries, without a blank line
+    let input = "================\nMLPerf Results Summary\n================\nSUT name : A\n================\nAdditional Stats\n================\n\n";
+    match parse_log_summary(input).unwrap_err() {
+        ParseError::UnexpectedToken {
+            location,
+            token,
+            expected,
+            ..
+        } => {
+            assert_eq!(location.line, 5);
+            assert_eq!(location.column, 1);
+            assert_eq!(location.source, "================");
+            assert_eq!(token, "SectionSeparatorLine");
+            assert!(expected.contains(&"EndOfSection".to_string()));
+        }
+        e => panic!("expected UnexpectedToken, got {:?}", e),
+    }
+
+    // truncated before the blank line that closes the section
+    let input = "================\nMLPerf Results Summary\n================\nSUT name : A\n";
+    match parse_log_summary(input).unwrap_err() {
+        ParseError::UnexpectedEof { location, .. } => {
+            assert_eq!(location.line, 4);
+            assert_eq!(location.source, "SUT name : A");
+        }
+        e => panic!("expected UnexpectedEof, got {:?}", e),
+    }
+
+    // last line without a newline cannot be tokenized
+    let input = "\nSUT name : A";
+    let e = parse_log_summary(input).unwrap_err();
+    match &e {
+        ParseError::Lexical {
+            location, at_eof, ..
+        } => {
+            assert_eq!(location.line, 2);
+            assert_eq!(location.source, "SUT name : A");
+            assert!(at_eof);
+        }
+        e => panic!("expected Lexical, got {:?}", e),
+    }
+    assert!(e
+        .to_string()
+        .ends_with("= hint: the last line of the log must end with a newline"));
+
+    let e = parse_log_summary_file("tests/data/does_not_exist.txt").unwrap_err();
+    assert!(matches!(e, ParseError::Io { .. }));
+    assert_eq!(
+        e.path().unwrap().to_str(),
+        Some("tests/data/does_not_exist.txt")
+    );
+}
+
+#[test]
+fn test_parse_error_diagnostic() {
+    let input = "================\nMLPerf Results Summary\n================\nSUT name : A\n================\nAdditional Stats\n================\n\n";
+    let e = parse_log_summary(input)
+        .unwrap_err()
+        .with_path("summary.txt");
+    assert_eq!(
+        e.to_string(),
+        r#"error: unexpected section separator
+ --> summary.txt:5:1
+  |
+4 | SUT name : A
+5 | ================
+  | ^^^^^^^^^^^^^^^^ expected a line or a blank line
+  |
+  = hint: missing blank line after section; insert an empty line before the separator"#
+    );
+
+    let e = parse_log_summary("================\nMLPerf Results Summary\n").unwrap_err();
+    let rendered = e.to_string();
+    assert!(rendered.starts_with("error: unexpected end of file\n --> <input>:2:23\n"));
+    assert!(rendered.contains("= hint: the log looks truncated"));
+}
+
+#[test]
+fn test_summary_output_preserves_source_order() {
+    let mut first = Vec::new();
+    save_summary("tests/data/mlperf_log_summary.txt", &mut first, "json").unwrap();
+    let mut second = Vec::new();
+    save_summary("tests/data/mlperf_log_summary.txt", &mut second, "json").unwrap();
+    assert_eq!(first, second);
+
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary.txt").unwrap();
+    let dict = doc.to_dict();
+    let sections: Vec<&str> = dict.keys().map(|k| k.as_str()).collect();
+    assert_eq!(
+        sections,
+        vec![
+            "mlperf_results_summary",
+            "additional_stats",
+            "test_parameters_used",
+            "note"
+        ]
+    );
+
+    let output = String::from_utf8(first).unwrap();
+    let positions: Vec<usize> = [
+        "sut_name",
+        "scenario",
+        "mode",
+        "samples_per_second",
+        "result_is",
+        "min_latency_ns",
+        "99_90_percentile_latency_ns",
+        "batch_size",
+        "target_latency_ns",
+    ]
+    .iter()
+    .map(|key| output.find(&format!("\"{}\"", key)).unwrap())
+    .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_duplicate_key_policy() {
+    let input = r###"================================================
+Test Parameters Used
+================================================
+target_latency (ns): 100
+Target latency (ns): 200
+Result is : VALID
+  Min duration satisfied : Yes
+  Min duration satisfied : No
+
+"###;
+    let doc = parse_log_summary(input).unwrap();
+    let convert = |policy| {
+        doc.to_dict_with(&DictOptions {
+            duplicate_keys: policy,
+            ..Default::default()
+        })
+    };
+    let latency = |dict: &Dict| match &dict["test_parameters_used"] {
+        DictValue::Dict(section) => section["target_latency_ns"].clone(),
+        _ => panic!("section is not a dict"),
+    };
+
+    let last = convert(DuplicateKeyPolicy::KeepLast).unwrap();
+    assert_eq!(latency(&last.dict), DictValue::Value(Value::I64(200)));
+    assert_eq!(
+        last.warnings,
+        vec![
+            DuplicateKey {
+                scope: "test_parameters_used".to_string(),
+                key: "target_latency_ns".to_string()
+            },
+            DuplicateKey {
+                scope: "test_parameters_used.result_is.details".to_string(),
+                key: "min_duration_satisfied".to_string()
+            },
+        ]
+    );
+
+    let first = convert(DuplicateKeyPolicy::KeepFirst).unwrap();
+    assert_eq!(latency(&first.dict), DictValue::Value(Value::I64(100)));
+    assert_eq!(first.warnings.len(), 2);
+
+    let list = convert(DuplicateKeyPolicy::Collect).unwrap();
+    assert_eq!(
+        latency(&list.dict),
+        DictValue::List(vec![
+            DictValue::Value(Value::I64(100)),
+            DictValue::Value(Value::I64(200))
+        ])
+    );
+
+    let error = convert(DuplicateKeyPolicy::Error).unwrap_err();
+    assert_eq!(error.key, "target_latency_ns");
+}
+
+#[test]
+fn test_unit_modes() {
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary.txt").unwrap();
+    let convert = |units| {
+        doc.to_dict_with(&DictOptions {
+            units,
+            ..Default::default()
+        })
+        .unwrap()
+        .dict
+    };
+    let section = |dict: &Dict, title: &str| match &dict[title] {
+        DictValue::Dict(section) => section.clone(),
+        _ => panic!("section is not a dict"),
+    };
+
+    let strip = convert(UnitMode::Strip);
+    let params = section(&strip, "test_parameters_used");
+    assert_eq!(
+        params["quality_target"],
+        DictValue::Value(Value::String("99%".to_string()))
+    );
+    assert_eq!(
+        section(&strip, "additional_stats")["min_latency_ns"],
+        DictValue::Value(Value::I64(123456))
+    );
+
+    let annotate = convert(UnitMode::Annotate);
+    let stats = section(&annotate, "additional_stats");
+    assert!(!stats.contains_key("min_latency_ns"));
+    assert_eq!(
+        stats["min_latency"],
+        DictValue::Dict(Dict::from([
+            ("value".to_string(), DictValue::Value(Value::I64(123456))),
+            (
+                "unit".to_string(),
+                DictValue::Value(Value::String("ns".to_string()))
+            ),
+        ]))
+    );
+    let params = section(&annotate, "test_parameters_used");
+    assert_eq!(params["quality_target"], DictValue::Value(Value::F64(0.99)));
+
+    let ms = convert(UnitMode::Normalize(TimeUnit::Ms));
+    let stats = section(&ms, "additional_stats");
+    assert_eq!(
+        stats["min_latency_ms"],
+        DictValue::Value(Value::F64(0.123456))
+    );
+    assert_eq!(
+        section(&ms, "test_parameters_used")["target_latency_ms"],
+        DictValue::Value(Value::F64(10.0))
+    );
+
+    let ns = convert(UnitMode::Normalize(TimeUnit::Ns));
+    assert_eq!(
+        section(&ns, "additional_stats")["99_90_percentile_latency_ns"],
+        DictValue::Value(Value::I64(890123))
+    );
+
+    assert_eq!("annotate".parse(), Ok(UnitMode::Annotate));
+    assert_eq!("us".parse(), Ok(UnitMode::Normalize(TimeUnit::Us)));
+    assert!("minutes".parse::<UnitMode>().is_err());
+}
+
+#[test]
+fn test_numeric_types() {
+    let dict = parse_log_summary_file("tests/data/mlperf_log_summary_singlestream.txt")
+        .unwrap()
+        .to_dict();
+    let params = match &dict["test_parameters_used"] {
+        DictValue::Dict(section) => section,
+        _ => panic!("section is not a dict"),
+    };
+    let value = |key: &str| params[key].clone();
+
+    // seeds are unsigned whether or not they fit in an i64
+    assert_eq!(
+        value("sample_index_rng_seed"),
+        DictValue::Value(Value::U64(10688027786191513374))
+    );
+    assert_eq!(
+        value("qsl_rng_seed"),
+        DictValue::Value(Value::U64(3066443479025735752))
+    );
+    assert_eq!(
+        value("accuracy_log_rng_seed"),
+        DictValue::Value(Value::U64(0))
+    );
+    assert_eq!(value("target_qps"), DictValue::Value(Value::F64(1000.0)));
+    assert_eq!(
+        value("accuracy_log_probability"),
+        DictValue::Value(Value::F64(0.0))
+    );
+    assert_eq!(
+        value("min_duration_ms"),
+        DictValue::Value(Value::I64(600000))
+    );
+    assert_eq!(value("samples_per_query"), DictValue::Value(Value::I64(1)));
+
+    let stats = match &dict["additional_stats"] {
+        DictValue::Dict(section) => section,
+        _ => panic!("section is not a dict"),
+    };
+    assert_eq!(
+        stats["qps_w/o_loadgen_overhead"],
+        DictValue::Value(Value::F64(311.39))
+    );
+
+    assert_eq!(
+        model::number_type("schedule_rng_seed"),
+        Some(NumberType::Unsigned)
+    );
+    assert_eq!(
+        model::number_type("samples_per_second"),
+        Some(NumberType::Float)
+    );
+    assert_eq!(
+        model::number_type("99_00_percentile_latency_ns"),
+        Some(NumberType::Signed)
+    );
+    assert_eq!(
+        model::number_type("99_90_percentile_first_token_latency_ns"),
+        Some(NumberType::Signed)
+    );
+    // only known keys are typed, others are inferred from their value
+    assert_eq!(model::number_type("batch_size"), None);
+    assert_eq!(model::number_type("ttft_latency_ns"), None);
+    assert_eq!(model::number_type("12_34_percentile_latency_ns"), None);
+}
+
+#[test]
+fn test_save_summary_csv() {
+    let mut output = Vec::new();
+    save_summary("tests/data/mlperf_log_summary.txt", &mut output, "csv").unwrap();
+    let csv = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "key,value");
+    assert_eq!(lines[1], "mlperf_results_summary.sut_name,Sample System");
+    assert!(lines.contains(&"mlperf_results_summary.result_is.details.min_duration_satisfied,true"));
+    assert!(lines.contains(&"test_parameters_used.quality_target,99%"));
+    assert!(save_summary("tests/data/mlperf_log_summary.txt", &mut Vec::new(), "xml").is_err());
+}
+
+#[test]
+fn test_summary_report() {
+    let doc = parse_log_summary(
+        "================================================
+MLPerf Results Summary
+================================================
+SUT name : <Sample> System
+Scenario : Server
+Result is : INVALID
+  Performance constraints satisfied : NO
+  Min duration satisfied : Yes
+
+================================================
+Additional Stats
+================================================
+Min latency (ns)                : 123456
+
+Notes: a | b
+
+",
+    )
+    .unwrap();
+
+    let mut output = Vec::new();
+    report::write_markdown(&doc, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "## MLPerf Results Summary
+
+| Key | Value |
+| --- | --- |
+| SUT name | <Sample> System |
+| Scenario | Server |
+| Result is | **INVALID** |
+| &nbsp;&nbsp;&nbsp;&nbsp;Performance constraints satisfied | **NO** |
+| &nbsp;&nbsp;&nbsp;&nbsp;Min duration satisfied | Yes |
+
+## Additional Stats
+
+| Key | Value |
+| --- | --- |
+| Min latency (ns) | 123456 |
+
+Notes: a | b
+"
+    );
+
+    let mut output = Vec::new();
+    report::write_html(&doc, &mut output).unwrap();
+    let html = String::from_utf8(output).unwrap();
+    assert!(html.starts_with("<h2>MLPerf Results Summary</h2>\n<table>\n"));
+    assert!(html.contains("<tr><td>SUT name</td><td>&lt;Sample&gt; System</td></tr>"));
+    assert!(html.contains(
+        "<tr><td style=\"padding-left: 2em\">Performance constraints satisfied</td>\
+         <td style=\"color: #c62828; font-weight: bold\">NO</td></tr>"
+    ));
+    assert!(html.contains(
+        "<tr><td style=\"padding-left: 2em\">Min duration satisfied</td><td>Yes</td></tr>"
+    ));
+    assert!(html.ends_with("</table>\n<p>Notes: a | b</p>\n"));
+
+    let mut output = Vec::new();
+    save_summary("tests/data/mlperf_log_summary.txt", &mut output, "markdown").unwrap();
+    assert!(String::from_utf8(output).unwrap().contains(
+        "| Result is | VALID |\n| &nbsp;&nbsp;&nbsp;&nbsp;Min duration satisfied | Yes |"
+    ));
+}
@@ -0,0 +1,131 @@
+use mlperf_log_parser::log_summary::stats::{summary_stats, StatsError, StatsRun};
+use mlperf_log_parser::parse_log_summary;
+use std::path::Path;
+use test_log::test;
+
+const RUN_1: &str = "================================================
+MLPerf Results Summary
+================================================
+SUT name : Sample System
+Scenario : Offline
+Mode     : PerformanceOnly
+Samples per second: 1000
+Result is : VALID
+
+================================================
+Additional Stats
+================================================
+99.00 percentile latency (ns)   : 200
+
+";
+
+const RUN_2: &str = "================================================
+MLPerf Results Summary
+================================================
+SUT name : Sample System
+Scenario : Offline
+Mode     : PerformanceOnly
+Samples per second: 1010
+Result is : VALID
+
+================================================
+Additional Stats
+================================================
+99.00 percentile latency (ns)   : 220
+
+";
+
+const RUN_3: &str = "================================================
+MLPerf Results Summary
+================================================
+SUT name : Sample System
+Scenario : Offline
+Mode     : PerformanceOnly
+Samples per second: 990
+Result is : VALID
+
+================================================
+Additional Stats
+================================================
+99.00 percentile latency (ns)   : 210
+
+";
+
+fn run(model: &str, log: &str) -> StatsRun {
+    StatsRun {
+        model: Some(model.to_string()),
+        summary: parse_log_summary(log).unwrap().to_dict(),
+    }
+}
+
+#[test]
+fn test_summary_stats() {
+    let runs = [RUN_1, RUN_2, RUN_3].map(|log| run("resnet50", log));
+    let stats = summary_stats(&runs).unwrap();
+    assert_eq!(stats.len(), 2);
+
+    let throughput = &stats[0];
+    assert_eq!(throughput.key, "mlperf_results_summary.samples_per_second");
+    assert_eq!(throughput.count, 3);
+    assert_eq!(throughput.mean, 1000.0);
+    assert_eq!(throughput.min, 990.0);
+    assert_eq!(throughput.max, 1010.0);
+    assert_eq!(throughput.stddev, 10.0);
+    assert_eq!(throughput.cv, Some(0.01));
+    assert_eq!(
+        throughput.to_string(),
+        "mlperf_results_summary.samples_per_second: mean 1000, min 990, max 1010, \
+         stddev 10, cv 1.00% (3 runs)"
+    );
+
+    let p99 = &stats[1];
+    assert_eq!(p99.key, "additional_stats.99_00_percentile_latency_ns");
+    assert_eq!((p99.mean, p99.min, p99.max), (210.0, 200.0, 220.0));
+
+    let single = summary_stats(&runs[..1]).unwrap();
+    assert_eq!(single[0].stddev, 0.0);
+    assert_eq!(single[0].count, 1);
+
+    // the fixture has more keys, all numeric ones are reported
+    let run = StatsRun::from_file(Path::new("tests/data/mlperf_log_summary.txt")).unwrap();
+    assert_eq!(run.model, None);
+    let stats = summary_stats(&[run.clone(), run]).unwrap();
+    assert!(stats
+        .iter()
+        .any(|s| s.key == "test_parameters_used.target_latency_ns" && s.count == 2));
+}
+
+#[test]
+fn test_summary_stats_mismatch() {
+    let server = RUN_3.replace("Offline", "Server");
+    let runs = [RUN_1, RUN_2, &server].map(|log| run("resnet50", log));
+    let err = summary_stats(&runs).unwrap_err();
+    assert_eq!(
+        err,
+        StatsError::Mismatch {
+            key: "mlperf_results_summary.scenario".to_string(),
+            expected: Some("Offline".to_string()),
+            found: Some("Server".to_string()),
+            run: 2,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "runs are not of the same configuration: 'mlperf_results_summary.scenario' \
+         is Offline in run 1 but Server in run 3"
+    );
+
+    let runs = [run("resnet50", RUN_1), run("bert-99", RUN_2)];
+    let err = summary_stats(&runs).unwrap_err();
+    assert_eq!(
+        err,
+        StatsError::Mismatch {
+            key: "model".to_string(),
+            expected: Some("resnet50".to_string()),
+            found: Some("bert-99".to_string()),
+            run: 1,
+        }
+    );
+
+    assert_eq!(summary_stats(&[]).unwrap_err(), StatsError::NoRuns);
+}
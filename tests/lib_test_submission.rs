@@ -0,0 +1,147 @@
+use mlperf_log_parser::submission::{scan_submission, RunKey};
+use mlperf_log_parser::Scenario;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+use test_log::test;
+
+/// Directory under the system temp directory, unique to a test run and
+/// removed when dropped, even when the test fails.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        TempDir(std::env::temp_dir().join(format!(
+            "mlperf-log-parser-{}-{}-{}",
+            name,
+            process::id(),
+            nanos
+        )))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Lays out a small submission in a new temp directory.
+fn submission_dir(name: &str) -> TempDir {
+    let dir = TempDir::new(name);
+    let root = dir.0.clone();
+    let copy = |dir: &Path, files: &[(&str, &str)]| {
+        fs::create_dir_all(dir).unwrap();
+        for (from, to) in files {
+            fs::copy(Path::new("tests/data").join(from), dir.join(to)).unwrap();
+        }
+    };
+
+    let results = root.join("results").join("sys-a").join("resnet50");
+    copy(
+        &results.join("Offline/performance/run_1"),
+        &[
+            ("mlperf_log_summary.txt", "mlperf_log_summary.txt"),
+            ("mlperf_log_detail.txt", "mlperf_log_detail.txt"),
+        ],
+    );
+    copy(
+        &results.join("SingleStream/performance/run_1"),
+        &[(
+            "mlperf_log_summary_singlestream.txt",
+            "mlperf_log_summary.txt",
+        )],
+    );
+    let accuracy = results.join("Offline/accuracy");
+    fs::create_dir_all(&accuracy).unwrap();
+    fs::write(
+        accuracy.join("mlperf_log_accuracy.json"),
+        r#"[{"seq_id": 0, "qsl_idx": 1, "data": "00"}, {"seq_id": 1, "qsl_idx": 0, "data": "01"}]"#,
+    )
+    .unwrap();
+    // broken logs are reported, not fatal
+    let broken = results.join("Server/performance/run_1");
+    fs::create_dir_all(&broken).unwrap();
+    fs::write(
+        broken.join("mlperf_log_summary.txt"),
+        "MLPerf Results Summary\n",
+    )
+    .unwrap();
+    // runs must sit right under results/, not deeper
+    copy(
+        &root.join("results/extra/sys-b/resnet50/Offline/performance"),
+        &[("mlperf_log_summary.txt", "mlperf_log_summary.txt")],
+    );
+    // a link back up the tree is not followed
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(root.join("results"), results.join("loop")).unwrap();
+    dir
+}
+
+#[test]
+fn test_scan_submission() {
+    let dir = submission_dir("scan");
+    let root = &dir.0;
+    let scan = scan_submission(root);
+
+    let keys: Vec<String> = scan.runs.keys().map(|k| k.to_string()).collect();
+    assert_eq!(
+        keys,
+        vec![
+            "sys-a/resnet50/Offline/accuracy",
+            "sys-a/resnet50/Offline/performance",
+            "sys-a/resnet50/Server/performance",
+            "sys-a/resnet50/SingleStream/performance",
+        ]
+    );
+
+    let key = RunKey {
+        system: "sys-a".to_string(),
+        model: "resnet50".to_string(),
+        scenario: "Offline".to_string(),
+        mode: "performance".to_string(),
+    };
+    let run = &scan.runs[&key][0];
+    assert_eq!(run.run.as_deref(), Some("run_1"));
+    assert_eq!(run.summary.as_ref().unwrap().scenario, Scenario::Offline);
+    assert_eq!(
+        run.settings.as_ref().unwrap().scenario,
+        Scenario::SingleStream
+    );
+    assert!(run.issues.is_empty());
+
+    let accuracy = scan.iter().find(|r| r.key.mode == "accuracy").unwrap();
+    assert_eq!(accuracy.run, None);
+    assert_eq!(accuracy.accuracy_entries, Some(2));
+
+    // logs outside the layout are skipped with a warning
+    assert_eq!(scan.warnings.len(), 1);
+    assert!(scan.warnings[0]
+        .path
+        .ends_with("extra/sys-b/resnet50/Offline/performance"));
+    assert_eq!(
+        scan.warnings[0].error,
+        "not in <system>/<model>/<scenario>/<mode> layout"
+    );
+    assert_eq!(scan.failures.len(), 1);
+    assert!(scan.failures[0]
+        .path
+        .ends_with("Server/performance/run_1/mlperf_log_summary.txt"));
+    let server = scan.iter().find(|r| r.key.scenario == "Server").unwrap();
+    assert_eq!(server.summary, None);
+
+    // the results directory itself can be scanned too
+    let scan = scan_submission(&root.join("results"));
+    assert_eq!(scan.runs.len(), 4);
+
+    let nested = Path::new("extra/sys-b/resnet50/Offline/performance/run_1");
+    assert_eq!(RunKey::from_dir(nested), None);
+    let (key, run) = RunKey::from_dir_tail(nested).unwrap();
+    assert_eq!(key.to_string(), "sys-b/resnet50/Offline/performance");
+    assert_eq!(run.as_deref(), Some("run_1"));
+}
@@ -0,0 +1,73 @@
+use mlperf_log_parser::log_summary::model::flatten_dict;
+use mlperf_log_parser::parse_log_summary_file;
+use mlperf_log_parser::table::{summary_row, Table, DEFAULT_COLUMNS};
+use serde_value::Value;
+use std::path::Path;
+use test_log::test;
+
+#[test]
+fn test_flatten_dict() {
+    let doc = parse_log_summary_file("tests/data/mlperf_log_summary.txt").unwrap();
+    let flat = flatten_dict(&doc.to_dict());
+    assert_eq!(
+        flat["mlperf_results_summary.result_is.details.min_duration_satisfied"],
+        Value::Bool(true)
+    );
+    assert_eq!(
+        flat["additional_stats.99_00_percentile_latency_ns"],
+        Value::I64(789012)
+    );
+    assert_eq!(
+        flat["note.0"],
+        Value::String("Notes: This is a sample result file for testing purposes.".to_string())
+    );
+}
+
+#[test]
+fn test_summary_table() {
+    let file = "tests/data/mlperf_log_summary.txt";
+    let doc = parse_log_summary_file(file).unwrap();
+    let mut columns: Vec<String> = DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect();
+    columns.push("test_parameters_used.model".to_string());
+    let mut table = Table::new(columns);
+    // off the layout, the system is unknown and the model is the one loadgen logs
+    table.push(&summary_row(Path::new(file), &doc));
+
+    // the layout gives the system and model when the path follows it
+    let path = Path::new("results/sys-a/resnet50/Offline/performance/run_1/mlperf_log_summary.txt");
+    table.push(&summary_row(path, &doc));
+
+    let mut csv = Vec::new();
+    table.write(&mut csv, "csv").unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "\
+system,model,scenario,mode,primary_metric,primary_metric_value,validity,p90_latency_ns,p99_latency_ns,test_parameters_used.model
+,ResNet50 v1.5,Offline,Performance,samples_per_second,1234.56,VALID,456789,789012,ResNet50 v1.5
+sys-a,resnet50,Offline,Performance,samples_per_second,1234.56,VALID,456789,789012,ResNet50 v1.5
+"
+    );
+
+    let mut table = Table::new(vec!["system".to_string(), "note".to_string()]);
+    table.push(
+        &[
+            ("system".to_string(), "a|b".to_string()),
+            ("note".to_string(), "x, \"y\"".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    let mut markdown = Vec::new();
+    table.write(&mut markdown, "markdown").unwrap();
+    assert_eq!(
+        String::from_utf8(markdown).unwrap(),
+        "| system | note |\n| --- | --- |\n| a\\|b | x, \"y\" |\n"
+    );
+    let mut csv = Vec::new();
+    table.write_csv(&mut csv).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "system,note\na|b,\"x, \"\"y\"\"\"\n"
+    );
+    assert!(table.write(&mut Vec::new(), "xlsx").is_err());
+}
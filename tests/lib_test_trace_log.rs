@@ -0,0 +1,121 @@
+use mlperf_log_parser::json_stream::{ElementKind, JsonStreamError};
+use mlperf_log_parser::log_summary::summary::{LatencyPercentile, LatencyStats};
+use mlperf_log_parser::trace_log::{
+    compare_latency_stats, query_latencies, trace_latencies_file, LatencyMismatch, SAMPLE_EVENT,
+};
+use mlperf_log_parser::{TraceEvent, TraceLogReader};
+use test_log::test;
+
+#[test]
+fn test_trace_log_reader() {
+    let events: Vec<TraceEvent> = TraceLogReader::open("tests/data/mlperf_log_trace.json")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(events.len(), 10);
+    assert_eq!(events[1].name, "IssueQuery");
+    assert_eq!(events[1].ph, "X");
+    assert_eq!(events[1].dur, Some(2.5));
+    assert_eq!(events[1].tid, Some(2));
+    assert_eq!(events[2].cat.as_deref(), Some("default"));
+    assert_eq!(events[2].args["sample_idx"], 7);
+    assert_eq!(events[9].name, "LastTrace");
+    assert_eq!(events[9].ts, None);
+
+    // a bare array of events
+    let text = r#"[{"name": "Sample", "ph": "X", "ts": 1, "dur": 2}]"#;
+    let events: Vec<_> = TraceLogReader::new(text.as_bytes()).collect();
+    assert!(matches!(events.as_slice(), [Ok(e)] if e.dur == Some(2.0)));
+
+    // numbers and literals before the events key
+    let text = r#"{"version": 1, "done": true,"traceEvents": [{"name": "A", "ph": "i"}]}"#;
+    let events: Vec<_> = TraceLogReader::new(text.as_bytes()).collect();
+    assert!(matches!(events.as_slice(), [Ok(e)] if e.name == "A"));
+    let text = r#"{"version": 1 "traceEvents": []}"#;
+    let events: Vec<_> = TraceLogReader::new(text.as_bytes()).collect();
+    assert!(matches!(
+        events.as_slice(),
+        [Err(JsonStreamError::Malformed {
+            expected: "',' or '}'",
+            found: Some('"'),
+            ..
+        })]
+    ));
+
+    let text = r#"{"displayTimeUnit": "ns", "otherData": {}}"#;
+    let events: Vec<_> = TraceLogReader::new(text.as_bytes()).collect();
+    match events.as_slice() {
+        [Err(
+            e @ JsonStreamError::Malformed {
+                kind: ElementKind::Event,
+                index: 0,
+                ..
+            },
+        )] => assert_eq!(
+            e.to_string(),
+            "malformed trace log (event 0): expected an object key, found '}'"
+        ),
+        other => panic!("unexpected events: {:?}", other),
+    }
+}
+
+#[test]
+fn test_query_latencies() {
+    let latencies = trace_latencies_file("tests/data/mlperf_log_trace.json", SAMPLE_EVENT).unwrap();
+    let ids: Vec<&str> = latencies.latencies.iter().map(|l| l.id.as_str()).collect();
+    assert_eq!(ids, vec!["1", "0", "2"]);
+    assert_eq!(latencies.latencies[1].latency_ns(), 300_000);
+    // id 3 never completed
+    assert_eq!(latencies.unmatched, 1);
+
+    let stats = latencies.stats().unwrap();
+    assert_eq!(stats.min_ns, 100_000);
+    assert_eq!(stats.max_ns, 300_000);
+    assert_eq!(stats.mean_ns, 200_000);
+    assert_eq!(stats.percentile(50.0), Some(200_000));
+    assert_eq!(stats.percentile(90.0), Some(300_000));
+
+    let events: Vec<TraceEvent> = TraceLogReader::open("tests/data/mlperf_log_trace.json")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let issue = query_latencies(&events, "IssueQuery");
+    assert_eq!(issue.latencies.len(), 1);
+    assert_eq!(issue.latencies[0].latency_ns(), 2_500);
+    assert!(query_latencies(&events, "Unknown").stats().is_none());
+}
+
+#[test]
+fn test_compare_latency_stats() {
+    let stats = |min_ns, p99| LatencyStats {
+        min_ns,
+        max_ns: 1_000_000,
+        mean_ns: 500_000,
+        percentiles: vec![
+            LatencyPercentile {
+                percentile: 50.0,
+                latency_ns: 400_000,
+            },
+            LatencyPercentile {
+                percentile: 99.0,
+                latency_ns: p99,
+            },
+        ],
+    };
+    let summary = stats(100_000, 900_000);
+    assert!(compare_latency_stats(&summary, &stats(100_500, 901_000), 0.01).is_empty());
+
+    let mismatches = compare_latency_stats(&summary, &stats(100_000, 990_000), 0.01);
+    assert_eq!(
+        mismatches,
+        vec![LatencyMismatch {
+            stat: "99.00 percentile".to_string(),
+            summary_ns: 900_000,
+            trace_ns: 990_000,
+        }]
+    );
+    assert_eq!(
+        mismatches[0].to_string(),
+        "99.00 percentile latency: summary has 900000 ns, trace log has 990000 ns"
+    );
+}